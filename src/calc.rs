@@ -0,0 +1,489 @@
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::{char, digit0, digit1, hex_digit1, multispace0, oct_digit1, one_of};
+use nom::combinator::{all_consuming, map, map_opt, map_res, opt, recognize};
+use nom::multi::{many0, separated_list1};
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
+use nom::IResult;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Time,
+    Data,
+    Volume,
+    Temperature,
+}
+
+#[derive(Debug, PartialEq)]
+struct Unit {
+    // the first one is used for display
+    names: &'static [&'static str],
+    dimension: Dimension,
+    // value in base units is `value * factor + offset`
+    factor: f64,
+    offset: f64,
+}
+
+macro_rules! units {
+    ($($dim:ident: [$($($name:literal)|+ => $factor:expr $(; $offset:expr)?),* $(,)?]),* $(,)?) => {
+        &[$($(Unit {
+            names: &[$($name),+],
+            dimension: Dimension::$dim,
+            factor: $factor,
+            offset: 0.0 $(+ $offset)?,
+        }),*),*]
+    };
+}
+
+static UNITS: &[Unit] = units![
+    Length: [
+        "mm" => 1e-3,
+        "cm" => 1e-2,
+        "m" => 1.0,
+        "km" => 1e3,
+        "in" | "inch" => 0.0254,
+        "ft" | "foot" | "feet" => 0.3048,
+        "yd" | "yard" => 0.9144,
+        "mi" | "mile" => 1609.344,
+    ],
+    Mass: [
+        "mg" => 1e-6,
+        "g" => 1e-3,
+        "kg" => 1.0,
+        "t" => 1e3,
+        "oz" => 0.028349523125,
+        "lb" => 0.45359237,
+    ],
+    Time: [
+        "ms" => 1e-3,
+        "s" | "sec" => 1.0,
+        "min" => 60.0,
+        "h" | "hour" => 3600.0,
+        "d" | "day" => 86400.0,
+        "week" => 604800.0,
+    ],
+    Data: [
+        "bit" => 0.125,
+        "B" | "byte" => 1.0,
+        "KB" | "kB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1048576.0,
+        "GiB" => 1073741824.0,
+        "TiB" => 1099511627776.0,
+    ],
+    Volume: [
+        "ml" => 1e-3,
+        "l" => 1.0,
+        "gal" => 3.785411784,
+    ],
+    Temperature: [
+        "K" => 1.0,
+        "C" | "°C" => 1.0; 273.15,
+        "F" | "°F" => 5.0 / 9.0; 459.67 * 5.0 / 9.0,
+    ],
+];
+
+fn find_unit(name: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|u| u.names.contains(&name))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Number(f64, Option<&'static Unit>),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(&'static str, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Target {
+    Unit(&'static Unit),
+    Hex,
+    Oct,
+    Bin,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Value {
+    number: f64,
+    unit: Option<&'static Unit>,
+}
+
+impl Value {
+    fn scalar(number: f64) -> Self {
+        Self { number, unit: None }
+    }
+
+    fn to_base(self) -> f64 {
+        self.unit
+            .map_or(self.number, |u| self.number * u.factor + u.offset)
+    }
+
+    fn convert(self, unit: &'static Unit) -> Result<Self, String> {
+        match self.unit {
+            Some(u) if u.dimension == unit.dimension => Ok(Self {
+                number: (self.to_base() - unit.offset) / unit.factor,
+                unit: Some(unit),
+            }),
+            Some(u) => Err(format!(
+                "cannot convert {} to {}",
+                u.names[0], unit.names[0]
+            )),
+            None => Err(format!("no unit to convert to {}", unit.names[0])),
+        }
+    }
+}
+
+const FUNCTIONS: &[&str] = &[
+    "sqrt", "abs", "sin", "cos", "tan", "asin", "acos", "atan", "ln", "log", "log2", "exp",
+    "floor", "ceil", "round", "min", "max",
+];
+
+fn ws<'a, O>(
+    inner: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    delimited(multispace0, inner, multispace0)
+}
+
+fn identifier(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '°' || c == '_')(input)
+}
+
+fn radix_number(input: &str) -> IResult<&str, f64> {
+    fn with_radix<'a>(
+        prefix: &'static str,
+        radix: u32,
+        digits: impl FnMut(&'a str) -> IResult<&'a str, &'a str>,
+    ) -> impl FnMut(&'a str) -> IResult<&'a str, f64> {
+        map_res(preceded(tag(prefix), digits), move |s| {
+            i64::from_str_radix(s, radix).map(|n| n as f64)
+        })
+    }
+
+    alt((
+        with_radix("0x", 16, hex_digit1),
+        with_radix("0o", 8, oct_digit1),
+        with_radix("0b", 2, take_while1(|c| c == '0' || c == '1')),
+    ))(input)
+}
+
+fn decimal_number(input: &str) -> IResult<&str, f64> {
+    map_res(
+        recognize(tuple((
+            alt((
+                recognize(pair(digit1, opt(pair(char('.'), digit0)))),
+                recognize(pair(char('.'), digit1)),
+            )),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+        ))),
+        str::parse,
+    )(input)
+}
+
+fn number(input: &str) -> IResult<&str, Expr> {
+    let (input, number) = alt((radix_number, decimal_number))(input)?;
+    let (input, unit) = opt(preceded(multispace0, map_opt(identifier, find_unit)))(input)?;
+    Ok((input, Expr::Number(number, unit)))
+}
+
+fn call(input: &str) -> IResult<&str, Expr> {
+    let (input, name) = map_opt(identifier, |name| {
+        FUNCTIONS.iter().find(|&&f| f == name).copied()
+    })(input)?;
+    let (input, args) = delimited(
+        ws(char('(')),
+        separated_list1(char(','), expr),
+        ws(char(')')),
+    )(input)?;
+    Ok((input, Expr::Call(name, args)))
+}
+
+fn constant(input: &str) -> IResult<&str, Expr> {
+    map_opt(identifier, |name| match name {
+        "pi" => Some(Expr::Number(std::f64::consts::PI, None)),
+        "e" => Some(Expr::Number(std::f64::consts::E, None)),
+        _ => None,
+    })(input)
+}
+
+fn atom(input: &str) -> IResult<&str, Expr> {
+    ws(alt((
+        number,
+        call,
+        constant,
+        delimited(char('('), expr, char(')')),
+    )))(input)
+}
+
+fn unary(input: &str) -> IResult<&str, Expr> {
+    alt((
+        map(preceded(ws(char('-')), unary), |e| Expr::Neg(Box::new(e))),
+        preceded(ws(char('+')), unary),
+        power,
+    ))(input)
+}
+
+fn power(input: &str) -> IResult<&str, Expr> {
+    let (input, base) = atom(input)?;
+    let (input, exp) = opt(preceded(char('^'), unary))(input)?;
+    Ok((
+        input,
+        match exp {
+            Some(exp) => Expr::Binary(Op::Pow, Box::new(base), Box::new(exp)),
+            None => base,
+        },
+    ))
+}
+
+fn fold_binary<'a>(
+    operand: fn(&'a str) -> IResult<&'a str, Expr>,
+    ops: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Expr> {
+    move |input| {
+        let (input, first) = operand(input)?;
+        let (input, rest) = many0(pair(one_of(ops), operand))(input)?;
+        let expr = rest.into_iter().fold(first, |lhs, (op, rhs)| {
+            let op = match op {
+                '+' => Op::Add,
+                '-' => Op::Sub,
+                '*' => Op::Mul,
+                '/' => Op::Div,
+                '%' => Op::Rem,
+                _ => unreachable!("unexpected operator {op}"),
+            };
+            Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+        });
+        Ok((input, expr))
+    }
+}
+
+fn term(input: &str) -> IResult<&str, Expr> {
+    fold_binary(unary, "*/%")(input)
+}
+
+fn expr(input: &str) -> IResult<&str, Expr> {
+    fold_binary(term, "+-")(input)
+}
+
+fn target(input: &str) -> IResult<&str, Target> {
+    preceded(
+        terminated(
+            alt((tag("to"), tag("in"), tag("as"), tag("->"))),
+            multispace0,
+        ),
+        map_opt(identifier, |name| match name {
+            "hex" => Some(Target::Hex),
+            "oct" => Some(Target::Oct),
+            "bin" => Some(Target::Bin),
+            _ => find_unit(name).map(Target::Unit),
+        }),
+    )(input)
+}
+
+fn parse(input: &str) -> Option<(Expr, Option<Target>)> {
+    all_consuming(pair(expr, opt(ws(target))))(input)
+        .ok()
+        .map(|(_, res)| res)
+}
+
+fn eval_call(name: &str, args: &[f64]) -> Result<f64, String> {
+    Ok(match (name, args) {
+        ("sqrt", [x]) => x.sqrt(),
+        ("abs", [x]) => x.abs(),
+        ("sin", [x]) => x.sin(),
+        ("cos", [x]) => x.cos(),
+        ("tan", [x]) => x.tan(),
+        ("asin", [x]) => x.asin(),
+        ("acos", [x]) => x.acos(),
+        ("atan", [x]) => x.atan(),
+        ("ln", [x]) => x.ln(),
+        ("log", [x]) => x.log10(),
+        ("log2", [x]) => x.log2(),
+        ("exp", [x]) => x.exp(),
+        ("floor", [x]) => x.floor(),
+        ("ceil", [x]) => x.ceil(),
+        ("round", [x]) => x.round(),
+        ("min", [x, rest @ ..]) => rest.iter().copied().fold(*x, f64::min),
+        ("max", [x, rest @ ..]) => rest.iter().copied().fold(*x, f64::max),
+        (name, args) => return Err(format!("{name} takes 1 argument, {} given", args.len())),
+    })
+}
+
+fn eval(expr: &Expr) -> Result<Value, String> {
+    Ok(match expr {
+        Expr::Number(number, unit) => Value {
+            number: *number,
+            unit: *unit,
+        },
+        Expr::Neg(e) => {
+            let v = eval(e)?;
+            Value {
+                number: -v.number,
+                ..v
+            }
+        }
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|a| match eval(a)? {
+                    Value { number, unit: None } => Ok(number),
+                    Value { unit: Some(_), .. } => Err(format!("{name} argument has a unit")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Value::scalar(eval_call(name, &args)?)
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let (lhs, rhs) = (eval(lhs)?, eval(rhs)?);
+            match (op, lhs.unit, rhs.unit) {
+                (_, None, None) => Value::scalar(match op {
+                    Op::Add => lhs.number + rhs.number,
+                    Op::Sub => lhs.number - rhs.number,
+                    Op::Mul => lhs.number * rhs.number,
+                    Op::Div => lhs.number / rhs.number,
+                    Op::Rem => lhs.number % rhs.number,
+                    Op::Pow => lhs.number.powf(rhs.number),
+                }),
+                (Op::Add | Op::Sub, Some(unit), Some(_)) => {
+                    let rhs = rhs.convert(unit)?.number;
+                    Value {
+                        number: if *op == Op::Add {
+                            lhs.number + rhs
+                        } else {
+                            lhs.number - rhs
+                        },
+                        unit: Some(unit),
+                    }
+                }
+                (Op::Mul, Some(unit), None) | (Op::Mul, None, Some(unit)) => Value {
+                    number: lhs.number * rhs.number,
+                    unit: Some(unit),
+                },
+                (Op::Div, Some(unit), None) => Value {
+                    number: lhs.number / rhs.number,
+                    unit: Some(unit),
+                },
+                (Op::Div, Some(l), Some(r)) if l.dimension == r.dimension => {
+                    Value::scalar(lhs.to_base() / rhs.to_base())
+                }
+                _ => return Err("unsupported operation on units".into()),
+            }
+        }
+    })
+}
+
+fn format_number(number: f64) -> String {
+    if number != 0. && number.abs() < 1e-6 {
+        return format!("{number:e}");
+    }
+    let s = format!("{number:.10}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".into()
+    } else {
+        s.into()
+    }
+}
+
+fn format_radix(number: f64, target: Target) -> Result<String, String> {
+    if number.fract() != 0. || number.abs() > i64::MAX as f64 {
+        return Err("only integers can be shown in hex, oct or bin".into());
+    }
+    let (sign, n) = if number < 0. {
+        ("-", -number)
+    } else {
+        ("", number)
+    };
+    let n = n as i64;
+    Ok(match target {
+        Target::Hex => format!("{sign}{n:#x}"),
+        Target::Oct => format!("{sign}{n:#o}"),
+        Target::Bin => format!("{sign}{n:#b}"),
+        Target::Unit(_) => unreachable!(),
+    })
+}
+
+/// Evaluates the arithmetic expression, e.g. `2 * (3 + 0x10)` or `5 km to mi`.
+/// Returns `None` if the input is not an expression at all and an error
+/// message if it cannot be evaluated.
+pub fn evaluate(input: &str) -> Option<Result<String, String>> {
+    let (expr, target) = parse(input)?;
+
+    Some(eval(&expr).and_then(|value| {
+        let value = match target {
+            Some(Target::Unit(unit)) => value.convert(unit)?,
+            Some(radix) if value.unit.is_none() => return format_radix(value.number, radix),
+            Some(_) => return Err("cannot show a unit value in hex, oct or bin".into()),
+            None => value,
+        };
+
+        Ok(match value.unit {
+            Some(unit) => format!("{} {}", format_number(value.number), unit.names[0]),
+            None => format_number(value.number),
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("1 + 2", "3"; "addition")]
+    #[test_case("2 + 3 * 4", "14"; "precedence")]
+    #[test_case("(2 + 3) * 4", "20"; "parentheses")]
+    #[test_case("2 ^ 3 ^ 2", "512"; "right assoc power")]
+    #[test_case("-2 ^ 2", "-4"; "negated power")]
+    #[test_case("10 / 4", "2.5"; "division")]
+    #[test_case("7 % 4", "3"; "remainder")]
+    #[test_case("0.1 + 0.2", "0.3"; "rounding")]
+    #[test_case("0x10 + 0b101 + 0o7", "28"; "radix literals")]
+    #[test_case("1e3 / 4", "250"; "exponent")]
+    #[test_case("sqrt(16) + abs(-2)", "6"; "functions")]
+    #[test_case("max(1, 5, 3)", "5"; "multiple arguments")]
+    #[test_case("round(pi * 100)", "314"; "constants")]
+    #[test_case("255 to hex", "0xff"; "to hex")]
+    #[test_case("-5 to bin", "-0b101"; "negative to bin")]
+    #[test_case("5 km to mi", "3.1068559612 mi"; "length conversion")]
+    #[test_case("3 in to cm", "7.62 cm"; "inch to cm")]
+    #[test_case("1 GiB in MiB", "1024 MiB"; "data conversion")]
+    #[test_case("100 C to F", "212 F"; "temperature conversion")]
+    #[test_case("1 km + 500 m", "1.5 km"; "units sum")]
+    #[test_case("2 h / 30 min", "4"; "units ratio")]
+    #[test_case("3 * 2 kg", "6 kg"; "scaled unit")]
+    fn test_evaluate(input: &str, expected: &str) {
+        assert_eq!(evaluate(input), Some(Ok(expected.to_string())));
+    }
+
+    #[test_case(""; "empty")]
+    #[test_case("firefox"; "word")]
+    #[test_case("1 +"; "incomplete")]
+    #[test_case("(1 + 2"; "unbalanced")]
+    #[test_case("5 km to"; "missing target")]
+    fn test_not_expression(input: &str) {
+        assert_eq!(evaluate(input), None);
+    }
+
+    #[test_case("1 km + 1 kg"; "incompatible units")]
+    #[test_case("1.5 to hex"; "fractional hex")]
+    #[test_case("sqrt(1, 2)"; "wrong arity")]
+    #[test_case("5 to km"; "no unit")]
+    fn test_error(input: &str) {
+        assert!(matches!(evaluate(input), Some(Err(_))));
+    }
+}
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+use sctk::seat::keyboard::{Keysym, Modifiers};
+use xkbcommon::xkb;
+
+/// Key with modifiers like `alt+1` or `ctrl+shift+Delete`, keys are named as
+/// xkb keysyms case-insensitively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chord {
+    keysym: Keysym,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    logo: bool,
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = match s.rsplit_once('+') {
+            // `+` itself is the key
+            Some((modifiers, "")) => (modifiers.strip_suffix('+').unwrap_or(modifiers), "plus"),
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", s),
+        };
+
+        let keysym = xkb::keysym_from_name(key, xkb::KEYSYM_CASE_INSENSITIVE);
+        if keysym == Keysym::NoSymbol {
+            return Err(format!("unknown key `{key}`"));
+        }
+
+        let mut chord = Chord {
+            keysym,
+            ctrl: false,
+            alt: false,
+            shift: false,
+            logo: false,
+        };
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut chord.ctrl,
+                "alt" => &mut chord.alt,
+                "shift" => &mut chord.shift,
+                "super" | "logo" => &mut chord.logo,
+                _ => return Err(format!("unknown modifier `{modifier}`")),
+            };
+            *flag = true;
+        }
+        Ok(chord)
+    }
+}
+
+impl Chord {
+    pub fn matches(&self, keysym: Keysym, modifiers: Modifiers) -> bool {
+        // Shifted letters come as upper case keysyms.
+        let same_key = self.keysym == keysym
+            || matches!(
+                (self.keysym.key_char(), keysym.key_char()),
+                (Some(x), Some(y)) if x.to_lowercase().eq(y.to_lowercase())
+            );
+
+        same_key
+            && self.ctrl == modifiers.ctrl
+            && self.alt == modifiers.alt
+            && self.shift == modifiers.shift
+            && self.logo == modifiers.logo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn modifiers(ctrl: bool, alt: bool, shift: bool) -> Modifiers {
+        Modifiers {
+            ctrl,
+            alt,
+            shift,
+            ..Default::default()
+        }
+    }
+
+    #[test_case("alt+1", Keysym::_1, modifiers(false, true, false); "alt digit")]
+    #[test_case("ctrl+shift+d", Keysym::D, modifiers(true, false, true); "shifted letter")]
+    #[test_case("Ctrl+delete", Keysym::Delete, modifiers(true, false, false); "named key")]
+    #[test_case("alt++", Keysym::plus, modifiers(false, true, false); "plus key")]
+    #[test_case("F2", Keysym::F2, modifiers(false, false, false); "no modifiers")]
+    fn test_matches(chord: &str, keysym: Keysym, modifiers: Modifiers) {
+        let chord: Chord = chord.parse().unwrap();
+        assert!(chord.matches(keysym, modifiers));
+    }
+
+    #[test_case("alt+1", Keysym::_1, modifiers(true, true, false); "extra modifier")]
+    #[test_case("alt+1", Keysym::_2, modifiers(false, true, false); "other key")]
+    #[test_case("alt+d", Keysym::D, modifiers(false, true, true); "missing shift")]
+    fn test_not_matches(chord: &str, keysym: Keysym, modifiers: Modifiers) {
+        let chord: Chord = chord.parse().unwrap();
+        assert!(!chord.matches(keysym, modifiers));
+    }
+
+    #[test_case("alt+nokey")]
+    #[test_case("hyper+a")]
+    #[test_case("")]
+    fn test_invalid(chord: &str) {
+        assert!(chord.parse::<Chord>().is_err());
+    }
+}
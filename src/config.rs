@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::path::PathBuf;
 
@@ -12,6 +13,7 @@ const DEFAULT_CONFIG_NAME: &str = concat!(crate::prog_name!(), ".config");
 
 const DEFAULT_ICON_SIZE: u16 = 16;
 const DEFAULT_FONT_SIZE: u16 = 24;
+const DEFAULT_TAB_FONT_SIZE: u16 = 16;
 
 const DEFAULT_FONT_COLOR: Color = Color::from_rgba(0xf8, 0xf8, 0xf2, 0xff);
 const DEFAULT_BG_COLOR: Color = Color::from_rgba(0x27, 0x28, 0x22, 0xee);
@@ -46,7 +48,11 @@ pub struct Config {
     corner_radius: Radius,
 
     icon: Option<Icon>,
+    modes: Vec<String>,
+    calc_prefix: bool,
+    menu: HashMap<String, Vec<MenuEntry>>,
 
+    tab_bar: TabBar,
     input_text: InputText,
     list_items: ListItems,
     mouse: Mouse,
@@ -72,6 +78,47 @@ impl Config {
     pub fn is_height_adaptive(&self) -> bool {
         self.adaptive_height
     }
+
+    pub fn modes(&self) -> &[String] {
+        &self.modes
+    }
+
+    pub fn menu(&self, name: &str) -> Option<&[MenuEntry]> {
+        self.menu.get(name).map(Vec::as_slice)
+    }
+
+    pub fn calc_prefix(&self) -> bool {
+        // Don't leak typed passwords to the calculator entry.
+        self.calc_prefix && !self.input_text.password
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct MenuEntry {
+    pub name: String,
+    pub subname: Option<String>,
+    pub icon: Option<String>,
+    pub exec: String,
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+#[derive(Defaults, Deserialize)]
+#[serde(default)]
+struct TabBar {
+    font: Option<String>,
+    font_size: Option<u16>,
+    font_color: Option<Color>,
+    selected_font_color: Option<Color>,
+    selected_bg_color: Option<Color>,
+    #[def = "Margin { bottom: 0.0, ..Margin::all(5.0) }"]
+    margin: Margin,
+    #[def = "Padding::from_pair(2.0, 10.0)"]
+    padding: Padding,
+    #[def = "Radius::all(f32::MAX)"]
+    corner_radius: Radius,
+    #[def = "5.0"]
+    spacing: f32,
 }
 
 #[derive(Defaults, Deserialize)]
@@ -101,6 +148,7 @@ struct ListItems {
     font_color: Option<Color>,
     selected_font_color: Option<Color>,
     match_color: Option<Color>,
+    mark_color: Option<Color>,
     #[def = "Margin { top: 10.0, ..Margin::from_pair(5.0, 15.0) }"]
     margin: Margin,
     #[def = "false"]
@@ -7,7 +7,7 @@ use once_cell::unsync::Lazy;
 
 use super::*;
 use crate::desktop::IconConfig;
-use crate::draw::{BgParams, InputTextParams, ListParams};
+use crate::draw::{BgParams, InputTextParams, ListParams, TabBarParams};
 use crate::font::{Font, FontBackend, InnerFont};
 use crate::icon::Icon;
 use crate::window::{Params as WindowParams, PointerParams};
@@ -53,6 +53,32 @@ impl<'a> From<&'a Config> for InputTextParams<'a> {
     }
 }
 
+impl<'a> From<&'a Config> for TabBarParams {
+    fn from(config: &'a Config) -> TabBarParams {
+        TabBarParams {
+            font: select_conf!(config, tab_bar, font)
+                .map(font_by_name)
+                .unwrap_or_else(default_font),
+            font_size: config.tab_bar.font_size.unwrap_or(DEFAULT_TAB_FONT_SIZE),
+            font_color: select_conf!(config, tab_bar, font_color)
+                .copied()
+                .unwrap_or(DEFAULT_FONT_COLOR),
+            selected_font_color: config
+                .tab_bar
+                .selected_font_color
+                .unwrap_or(DEFAULT_SELECTED_FONT_COLOR),
+            selected_bg_color: config
+                .tab_bar
+                .selected_bg_color
+                .unwrap_or(DEFAULT_INPUT_BG_COLOR),
+            margin: config.tab_bar.margin.clone(),
+            padding: config.tab_bar.padding.clone(),
+            radius: config.tab_bar.corner_radius.clone(),
+            spacing: config.tab_bar.spacing,
+        }
+    }
+}
+
 impl<'a> From<&'a Config> for ListParams {
     fn from(config: &'a Config) -> ListParams {
         ListParams {
@@ -70,6 +96,11 @@ impl<'a> From<&'a Config> for ListParams {
                 .selected_font_color
                 .unwrap_or(DEFAULT_SELECTED_FONT_COLOR),
             match_color: config.list_items.match_color,
+            mark_color: config
+                .list_items
+                .mark_color
+                .or(config.list_items.selected_font_color)
+                .unwrap_or(DEFAULT_SELECTED_FONT_COLOR),
             icon_size: config.icon.as_ref().map(|c| c.size),
             fallback_icon: config
                 .icon
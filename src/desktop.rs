@@ -9,6 +9,7 @@ use xdg::BaseDirectories;
 use crate::icon::Icon;
 
 mod locale;
+pub mod mimeapps;
 
 pub static XDG_DIRS: OnceCell<BaseDirectories> = OnceCell::new();
 
@@ -25,6 +26,8 @@ pub struct Entry {
     pub path: PathBuf,
     pub name: String,
     pub is_terminal: bool,
+    pub mime_types: Vec<String>,
+    pub wm_class: Option<String>,
 }
 
 impl Entry {
@@ -44,6 +47,78 @@ impl Entry {
                 .or(self.entry.icon.as_ref())
         }
     }
+
+    /// Whether the entry belongs to a window with the Wayland `app_id` or X11
+    /// class, e.g. `org.gnome.Nautilus` or `firefox`.
+    pub fn matches_app_id(&self, app_id: &str) -> bool {
+        let fname = self.desktop_fname.trim_end_matches(".desktop");
+        fname.eq_ignore_ascii_case(app_id)
+            || self
+                .wm_class
+                .as_deref()
+                .is_some_and(|c| c.eq_ignore_ascii_case(app_id))
+            // Reverse-DNS desktop ids often end with the binary name.
+            || fname
+                .rsplit('.')
+                .next()
+                .is_some_and(|n| n.eq_ignore_ascii_case(app_id))
+    }
+}
+
+/// Splits `Exec` key value into arguments expanding field codes, see
+/// <https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html>.
+/// Files are passed to `%f`/`%u` (the first one) and `%F`/`%U` (all of them).
+pub fn expand_exec(exec: &str, files: &[&str]) -> anyhow::Result<Vec<String>> {
+    let args = shlex::split(exec).ok_or_else(|| anyhow::anyhow!("invalid command line: {exec}"))?;
+    let mut expanded = vec![];
+    let mut files_used = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "%F" | "%U" => {
+                expanded.extend(files.iter().map(|&f| f.to_owned()));
+                files_used = true;
+                continue;
+            }
+            "%f" | "%u" => {
+                expanded.extend(files.first().map(|&f| f.to_owned()));
+                files_used = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut value = String::with_capacity(arg.len());
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                value.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('%') => value.push('%'),
+                Some('f' | 'u' | 'F' | 'U') => {
+                    value.push_str(files.first().copied().unwrap_or_default());
+                    files_used = true;
+                }
+                // Icon, name and location codes as well as deprecated ones
+                // are dropped.
+                _ => {}
+            }
+        }
+
+        if !value.is_empty() || arg.is_empty() {
+            expanded.push(value);
+        }
+    }
+
+    // Like GLib, pass files anyway if the command has no place for them.
+    if !files_used {
+        expanded.extend(files.iter().map(|&f| f.to_owned()));
+    }
+
+    Ok(expanded)
 }
 
 pub fn xdg_dirs<'a>() -> &'a BaseDirectories {
@@ -55,31 +130,24 @@ pub struct IconConfig {
     pub theme: Option<String>,
 }
 
-pub struct Traverser<F> {
-    icon_config: Option<(IconConfig, Cache)>,
-    filter: F,
+pub struct IconLookup {
+    config: IconConfig,
+    cache: Cache,
 }
 
-impl<F> Traverser<F> {
-    pub fn new(icon_config: Option<IconConfig>, filter: F) -> anyhow::Result<Self> {
-        Ok(Self {
-            icon_config: icon_config
-                .map(|icon_config| -> anyhow::Result<_> {
-                    let mut lookup = Cache::new()?;
-                    if let Some(theme) = &icon_config.theme {
-                        lookup.load(theme)
-                    } else {
-                        lookup.load_default()
-                    }?;
-                    Ok((icon_config, lookup))
-                })
-                .transpose()?,
-            filter,
-        })
+impl IconLookup {
+    pub fn new(config: IconConfig) -> anyhow::Result<Self> {
+        let mut cache = Cache::new()?;
+        if let Some(theme) = &config.theme {
+            cache.load(theme)
+        } else {
+            cache.load_default()
+        }?;
+        Ok(Self { config, cache })
     }
 
-    fn find_icon(&self, name: &str) -> Option<Icon> {
-        let (config, lookup) = self.icon_config.as_ref()?;
+    pub(crate) fn find_icon(&self, name: &str) -> Option<Icon> {
+        let Self { config, cache } = self;
 
         let icon_path = Path::new(name);
         let path: Option<PathBuf>;
@@ -88,7 +156,7 @@ impl<F> Traverser<F> {
             Some(icon_path)
         } else {
             let lookup_icon = |name: &str, icon_size: u16| {
-                lookup.lookup_param(
+                cache.lookup_param(
                     LookupParam::new(name)
                         .with_size(icon_size)
                         .with_theme(config.theme.as_deref()),
@@ -110,6 +178,24 @@ impl<F> Traverser<F> {
 
         path.map(Icon::new)
     }
+}
+
+pub struct Traverser<F> {
+    icon_lookup: Option<IconLookup>,
+    filter: F,
+}
+
+impl<F> Traverser<F> {
+    pub fn new(icon_config: Option<IconConfig>, filter: F) -> anyhow::Result<Self> {
+        Ok(Self {
+            icon_lookup: icon_config.map(IconLookup::new).transpose()?,
+            filter,
+        })
+    }
+
+    fn find_icon(&self, name: &str) -> Option<Icon> {
+        self.icon_lookup.as_ref()?.find_icon(name)
+    }
 
     fn parse_entry(&self, dir_entry: &DirEntry, dir_entry_path: PathBuf) -> Option<Entry> {
         let entry = match fep::parse_entry(&dir_entry_path) {
@@ -178,6 +264,14 @@ impl<F> Traverser<F> {
                         .attr("Terminal")
                         .map(|s| s == "true")
                         .unwrap_or(false),
+                    mime_types: main_section
+                        .attr("MimeType")
+                        .into_iter()
+                        .flat_map(|m| m.split(';'))
+                        .filter(|m| !m.is_empty())
+                        .map(str::to_owned)
+                        .collect(),
+                    wm_class: main_section.attr("StartupWMClass").map(str::to_owned),
                 });
             }
             (n, e) => {
@@ -266,3 +360,22 @@ fn read_dir(path: &Path) -> impl Iterator<Item = DirEntry> {
             e.ok()
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("app %U", &[], &["app"]; "no files")]
+    #[test_case("app %f", &["/a b"], &["app", "/a b"]; "single file")]
+    #[test_case("app %F", &["/a", "/b"], &["app", "/a", "/b"]; "multiple files")]
+    #[test_case("app %u", &["/a", "/b"], &["app", "/a"]; "first file only")]
+    #[test_case("app --file=%f", &["/a"], &["app", "--file=/a"]; "embedded")]
+    #[test_case("app %i %c 100%%", &[], &["app", "100%"]; "other codes")]
+    #[test_case("app", &["/a"], &["app", "/a"]; "no field code")]
+    #[test_case("\"my app\" %f", &["/a"], &["my app", "/a"]; "quoted")]
+    fn test_expand_exec(exec: &str, files: &[&str], expected: &[&str]) {
+        assert_eq!(expand_exec(exec, files).unwrap(), expected);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::xdg_dirs;
+
+type Associations = HashMap<String, Vec<String>>;
+
+/// Applications associated with MIME types by `mimeapps.list` and
+/// `mimeinfo.cache` files, see
+/// <https://specifications.freedesktop.org/mime-apps-spec/latest/>.
+#[derive(Default)]
+pub struct MimeApps {
+    defaults: Associations,
+    added: Associations,
+    removed: Associations,
+    cached: Associations,
+}
+
+// Yields `(section, key, values)` of `key=value;value;` lines.
+fn parse_ini(content: &str) -> impl Iterator<Item = (&str, &str, Vec<&str>)> {
+    let mut section = "";
+    content.lines().filter_map(move |line| {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name;
+            return None;
+        }
+        if line.starts_with('#') {
+            return None;
+        }
+
+        let (key, values) = line.split_once('=')?;
+        let values = values
+            .split(';')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect();
+        Some((section, key.trim(), values))
+    })
+}
+
+fn extend(associations: &mut Associations, mime: &str, apps: Vec<&str>) {
+    let list = associations.entry(mime.to_owned()).or_default();
+    for app in apps {
+        if !list.iter().any(|a| a == app) {
+            list.push(app.to_owned());
+        }
+    }
+}
+
+impl MimeApps {
+    pub fn load() -> Self {
+        let mut mime_apps = Self::default();
+
+        for path in Self::list_paths() {
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                log::trace!("loading {}", path.display());
+                mime_apps.add_list(&content);
+            }
+        }
+
+        let xdg_dirs = xdg_dirs();
+        for dir in std::iter::once(xdg_dirs.get_data_home()).chain(xdg_dirs.get_data_dirs()) {
+            if let Ok(content) = std::fs::read_to_string(dir.join("applications/mimeinfo.cache")) {
+                mime_apps.add_cache(&content);
+            }
+        }
+
+        mime_apps
+    }
+
+    // Paths of `mimeapps.list` files from the most important one.
+    fn list_paths() -> Vec<PathBuf> {
+        let desktops: Vec<_> = std::env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(|d| format!("{}-mimeapps.list", d.to_lowercase()))
+            .collect();
+
+        let xdg_dirs = xdg_dirs();
+        let config_dirs =
+            std::iter::once(xdg_dirs.get_config_home()).chain(xdg_dirs.get_config_dirs());
+        let data_dirs = std::iter::once(xdg_dirs.get_data_home())
+            .chain(xdg_dirs.get_data_dirs())
+            .map(|d| d.join("applications"));
+
+        config_dirs
+            .chain(data_dirs)
+            .flat_map(|dir| {
+                desktops
+                    .iter()
+                    .map(String::as_str)
+                    .chain(["mimeapps.list"])
+                    .map(move |fname| dir.join(fname))
+            })
+            .collect()
+    }
+
+    fn add_list(&mut self, content: &str) {
+        for (section, mime, apps) in parse_ini(content) {
+            let associations = match section {
+                "Default Applications" => &mut self.defaults,
+                "Added Associations" => &mut self.added,
+                "Removed Associations" => &mut self.removed,
+                _ => continue,
+            };
+            extend(associations, mime, apps);
+        }
+    }
+
+    fn add_cache(&mut self, content: &str) {
+        for (section, mime, apps) in parse_ini(content) {
+            if section == "MIME Cache" {
+                extend(&mut self.cached, mime, apps);
+            }
+        }
+    }
+
+    /// Desktop file of the default application for the first of `mime_types`
+    /// having an installed one.
+    pub fn default_app<'a>(
+        &'a self,
+        mime_types: &[&str],
+        is_installed: impl Fn(&str) -> bool,
+    ) -> Option<&'a str> {
+        mime_types
+            .iter()
+            .flat_map(|&mime| self.defaults.get(mime).into_iter().flatten())
+            .map(String::as_str)
+            .find(|&app| is_installed(app))
+    }
+
+    /// Desktop files of applications associated with `mime_types`, in the
+    /// preference order.
+    pub fn associated<'a>(&'a self, mime_types: &[&str]) -> Vec<&'a str> {
+        let mut apps = vec![];
+        for &mime in mime_types {
+            let candidates = self.added.get(mime).into_iter().flatten().chain(
+                self.cached
+                    .get(mime)
+                    .into_iter()
+                    .flatten()
+                    .filter(|app| !self.is_removed(mime, app)),
+            );
+
+            for app in candidates {
+                if !apps.contains(&app.as_str()) {
+                    apps.push(app.as_str());
+                }
+            }
+        }
+        apps
+    }
+
+    pub fn is_removed(&self, mime: &str, app: &str) -> bool {
+        self.removed
+            .get(mime)
+            .is_some_and(|apps| apps.iter().any(|a| a == app))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_associations() {
+        let mut mime_apps = MimeApps::default();
+        mime_apps.add_list(
+            "\
+[Default Applications]
+text/plain=missing.desktop;editor.desktop;
+[Added Associations]
+text/plain=added.desktop;
+[Removed Associations]
+text/plain=removed.desktop
+",
+        );
+        mime_apps.add_list("[Default Applications]\ntext/plain=other.desktop\n");
+        mime_apps.add_cache(
+            "\
+[MIME Cache]
+text/plain=removed.desktop;cached.desktop;editor.desktop;
+text/x-csrc=ide.desktop;
+",
+        );
+
+        let types = ["text/x-csrc", "text/plain"];
+        assert_eq!(
+            mime_apps.default_app(&types, |app| app != "missing.desktop"),
+            Some("editor.desktop")
+        );
+        assert_eq!(
+            mime_apps.associated(&types),
+            [
+                "ide.desktop",
+                "added.desktop",
+                "cached.desktop",
+                "editor.desktop"
+            ]
+        );
+    }
+}
@@ -7,6 +7,7 @@ use raqote::{DrawOptions, Path, PathBuilder, Source, StrokeStyle};
 pub use background::Params as BgParams;
 pub use input_text::Params as InputTextParams;
 pub use list_view::{ListItem, Params as ListParams};
+pub use tab_bar::Params as TabBarParams;
 
 use crate::{style::Radius, Color};
 
@@ -15,6 +16,7 @@ pub type DrawTarget<'a> = raqote::DrawTarget<&'a mut [u32]>;
 mod background;
 mod input_text;
 mod list_view;
+mod tab_bar;
 
 #[derive(Clone, Copy)]
 pub struct Space {
@@ -32,6 +34,7 @@ pub enum Widget<'a, It = std::iter::Empty<ListItem<'a>>> {
     InputText(Box<input_text::InputText<'a>>),
     ListView(list_view::ListView<'a, It>),
     Background(background::Background),
+    TabBar(tab_bar::TabBar<'a>),
 }
 
 pub struct Drawables<'a> {
@@ -40,6 +43,7 @@ pub struct Drawables<'a> {
     rx: Option<oneshot::Receiver<usize>>,
     state: &'a mut crate::state::State,
     background_config: BgParams,
+    tab_bar_config: TabBarParams,
     input_config: InputTextParams<'a>,
     list_config: ListParams,
 }
@@ -49,8 +53,17 @@ impl<'a> Drawables<'a> {
         self.counter += 1;
         Some(match self.counter {
             1 => Widget::background(&self.background_config),
-            2 => Widget::input_text(self.state.raw_input(), &self.input_config),
-            3 => Widget::list_view(
+            2 => Widget::tab_bar(
+                self.state.mode_labels().collect(),
+                self.state.current_mode(),
+                &self.tab_bar_config,
+            ),
+            3 => Widget::input_text(
+                self.state.raw_input(),
+                self.state.is_loading(),
+                &self.input_config,
+            ),
+            4 => Widget::list_view(
                 self.state.processed_entries(),
                 self.state.skip_offset(),
                 self.state.selected_item(),
@@ -58,7 +71,7 @@ impl<'a> Drawables<'a> {
                 self.tx.take().unwrap(),
                 &self.list_config,
             ),
-            4 => {
+            5 => {
                 self.state
                     .update_skip_offset(self.rx.take().unwrap().recv().unwrap());
                 return None;
@@ -74,21 +87,23 @@ pub fn make_drawables<'c: 'it, 's: 'it, 'it>(
     scale: u16,
 ) -> (Drawables<'it>, Option<Space>) {
     let background_config = config.param();
+    let tab_bar_config: TabBarParams = config.param();
     let input_config: InputTextParams<'_> = config.param();
     let list_config: ListParams = config.param();
 
     state.process_entries();
 
     let space = if config.is_height_adaptive() {
+        let tab_bar_space = tab_bar_config.occupied_space(scale, state.mode_labels().len());
         let input_space = input_config.occupied_space(scale);
         let list_space = list_config.space_for_entries(
-            state.processed_entries().len().max(1),
+            state.entries_len().max(1),
             scale,
             state.selected_has_subitems(),
         );
         Some(Space {
             width: 0.,
-            height: input_space.height + list_space.height,
+            height: tab_bar_space.height + input_space.height + list_space.height,
         })
     } else {
         None
@@ -103,6 +118,7 @@ pub fn make_drawables<'c: 'it, 's: 'it, 'it>(
             state,
 
             background_config,
+            tab_bar_config,
             input_config,
             list_config,
         },
@@ -111,8 +127,8 @@ pub fn make_drawables<'c: 'it, 's: 'it, 'it>(
 }
 
 impl<'a, It> Widget<'a, It> {
-    pub fn input_text(text: &'a str, params: &'a InputTextParams<'a>) -> Self {
-        Self::InputText(Box::new(input_text::InputText::new(text, params)))
+    pub fn input_text(text: &'a str, loading: bool, params: &'a InputTextParams<'a>) -> Self {
+        Self::InputText(Box::new(input_text::InputText::new(text, loading, params)))
     }
 
     pub fn list_view(
@@ -136,6 +152,10 @@ impl<'a, It> Widget<'a, It> {
     pub fn background(params: &'a BgParams) -> Self {
         Self::Background(background::Background::new(params))
     }
+
+    pub fn tab_bar(labels: Vec<&'a str>, selected: usize, params: &'a TabBarParams) -> Self {
+        Self::TabBar(tab_bar::TabBar::new(labels, selected, params))
+    }
 }
 
 impl<'a, It> Drawable for Widget<'a, It>
@@ -147,6 +167,7 @@ where
             Self::InputText(w) => w.draw(dt, scale, space, start_point),
             Self::ListView(w) => w.draw(dt, scale, space, start_point),
             Self::Background(w) => w.draw(dt, scale, space, start_point),
+            Self::TabBar(w) => w.draw(dt, scale, space, start_point),
         }
     }
 }
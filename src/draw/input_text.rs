@@ -1,4 +1,6 @@
-use raqote::{DrawOptions, Point};
+use std::f32::consts;
+
+use raqote::{DrawOptions, PathBuilder, Point, Source};
 
 use super::{DrawTarget, Drawable, RoundedRect, Space};
 use crate::font::{Font, FontBackend, FontColor};
@@ -24,6 +26,8 @@ pub struct Params<'a> {
 
 pub struct InputText<'a> {
     text: &'a str,
+    // entries are still arriving
+    loading: bool,
     params: &'a Params<'a>,
     rect: RoundedRect,
 }
@@ -52,12 +56,13 @@ impl Params<'_> {
 }
 
 impl<'a> InputText<'a> {
-    pub fn new(text: &'a str, params: &'a Params<'a>) -> Self {
+    pub fn new(text: &'a str, loading: bool, params: &'a Params<'a>) -> Self {
         let color = params.bg_color;
         let radius = params.radius.clone();
 
         Self {
             text,
+            loading,
             params,
             rect: RoundedRect::new(radius, color),
         }
@@ -132,6 +137,25 @@ impl<'a> Drawable for InputText<'a> {
 
         // TODO: use padding.right for text wrapping/clipping
 
+        // Three dots at the right end until entries are loaded.
+        if self.loading {
+            let radius = font_size / 10.0;
+            let y = rect_point.y + rect_height / 2.0;
+            let mut x = rect_point.x + rect_width - padding.right - rect_height / 2.0;
+
+            let mut pb = PathBuilder::new();
+            for _ in 0..3 {
+                pb.move_to(x + radius, y);
+                pb.arc(x, y, radius, 0.0, 2.0 * consts::PI);
+                x -= 3.0 * radius;
+            }
+            dt.fill(
+                &pb.finish(),
+                &Source::Solid(self.params.prompt_color.as_source()),
+                &DrawOptions::new(),
+            );
+        }
+
         Space {
             width: space.width,
             height: margin.top + rect_height + margin.bottom,
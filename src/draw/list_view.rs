@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 
 use oneshot::Sender;
-use raqote::{AntialiasMode, DrawOptions, Image, Point};
+use raqote::{AntialiasMode, DrawOptions, Image, Point, Source};
 
 use super::{DrawTarget, Drawable, Space};
 use crate::font::{Font, FontBackend, FontColor};
@@ -16,6 +16,7 @@ pub struct Params {
     pub font_color: Color,
     pub selected_font_color: Color,
     pub match_color: Option<Color>,
+    pub mark_color: Color,
     pub icon_size: Option<u16>,
     pub fallback_icon: Option<crate::icon::Icon>,
     pub margin: Margin,
@@ -30,6 +31,7 @@ pub struct ListItem<'a> {
     pub subname: Option<&'a str>,
     pub icon: Option<Image<'a>>,
     pub match_mask: Option<ContinuousMatch<'a>>,
+    pub marked: bool,
 }
 
 pub struct ListView<'a, It> {
@@ -137,6 +139,19 @@ where
             let x_offset = point.x + margin.left;
             let y_offset = top_offset + relative_offset;
 
+            // Marked entries have a bar in the left margin.
+            if item.marked {
+                let bar_width = (margin.left / 3.).max(1.);
+                dt.fill_rect(
+                    point.x + bar_width,
+                    y_offset,
+                    bar_width,
+                    font_size,
+                    &Source::Solid(self.params.mark_color.as_source()),
+                    &DrawOptions::new(),
+                );
+            }
+
             let fallback_icon = self
                 .params
                 .fallback_icon
@@ -188,8 +203,9 @@ where
                 let special_len = special_color.len();
                 let mut last_idx = 0; // exclusive
 
+                // Searched text may be longer than the name, e.g. with keywords.
                 match_mask.for_each(|m| {
-                    let unmatch_range = last_idx..m.start();
+                    let unmatch_range = last_idx..m.start().min(special_len);
                     if !unmatch_range.is_empty() {
                         special_color[unmatch_range].fill(color);
                     }
@@ -249,6 +265,7 @@ mod tests {
             font_color: Color::from_rgba(15, 15, 15, 255),
             selected_font_color: Color::from_rgba(15, 15, 15, 255),
             match_color: None,
+            mark_color: Color::from_rgba(15, 15, 15, 255),
             icon_size: Some(16),
             fallback_icon: None,
             margin: Margin {
@@ -0,0 +1,111 @@
+use raqote::{DrawOptions, Point};
+
+use super::{DrawTarget, Drawable, RoundedRect, Space};
+use crate::font::{Font, FontBackend, FontColor};
+use crate::style::{Margin, Padding, Radius};
+use crate::Color;
+
+pub struct Params {
+    pub font: Font,
+    pub font_size: u16,
+    pub font_color: Color,
+    pub selected_font_color: Color,
+    pub selected_bg_color: Color,
+    pub margin: Margin,
+    pub padding: Padding,
+    pub radius: Radius,
+    pub spacing: f32,
+}
+
+pub struct TabBar<'a> {
+    labels: Vec<&'a str>,
+    selected: usize,
+    params: &'a Params,
+}
+
+impl Params {
+    pub fn occupied_space(&self, scale: u16, tabs_count: usize) -> Space {
+        if tabs_count < 2 {
+            return Space {
+                width: 0.,
+                height: 0.,
+            };
+        }
+
+        let padding = &self.padding * f32::from(scale);
+        let margin = &self.margin * f32::from(scale);
+        let font_size = f32::from(self.font_size * scale);
+        Space {
+            width: 0.,
+            height: margin.top + padding.top + font_size + padding.bottom + margin.bottom,
+        }
+    }
+}
+
+impl<'a> TabBar<'a> {
+    pub fn new(labels: Vec<&'a str>, selected: usize, params: &'a Params) -> Self {
+        Self {
+            labels,
+            selected,
+            params,
+        }
+    }
+}
+
+impl<'a> Drawable for TabBar<'a> {
+    fn draw(self, dt: &mut DrawTarget<'_>, scale: u16, space: Space, point: Point) -> Space {
+        let occupied = self.params.occupied_space(scale, self.labels.len());
+        if occupied.height == 0. {
+            return occupied;
+        }
+
+        let padding = &self.params.padding * f32::from(scale);
+        let margin = &self.params.margin * f32::from(scale);
+        let spacing = self.params.spacing * f32::from(scale);
+        let font_size = f32::from(self.params.font_size * scale);
+
+        // All tabs share the width equally, labels are cut if not fitted.
+        let count = self.labels.len() as f32;
+        let tab_width = (space.width - margin.left - margin.right - spacing * (count - 1.)) / count;
+        let tab_height = padding.top + font_size + padding.bottom;
+
+        for (i, label) in self.labels.into_iter().enumerate() {
+            let tab_point = Point::new(
+                point.x + margin.left + i as f32 * (tab_width + spacing),
+                point.y + margin.top,
+            );
+
+            let color = if i == self.selected {
+                RoundedRect::new(self.params.radius.clone(), self.params.selected_bg_color).draw(
+                    dt,
+                    scale,
+                    Space {
+                        width: tab_width,
+                        height: tab_height,
+                    },
+                    tab_point,
+                );
+                self.params.selected_font_color
+            } else {
+                self.params.font_color
+            };
+
+            let pos = Point::new(tab_point.x + padding.left, tab_point.y + padding.top);
+            let end_pos = Point::new(tab_point.x + tab_width - padding.right, pos.y);
+            self.params.font.draw(
+                dt,
+                label,
+                font_size,
+                pos,
+                end_pos,
+                FontColor::Single(color.as_source()),
+                &DrawOptions::new(),
+            );
+        }
+
+        Space {
+            width: space.width,
+            height: occupied.height,
+        }
+    }
+}
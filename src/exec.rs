@@ -24,17 +24,17 @@ pub fn exec(
 
     let command_iter = command_string.into_iter().map(Into::into);
 
-    let command: Vec<_> = if let Some(mut term) = term {
-        let mut command = command_iter.fold(Vec::new(), |mut v, item| {
-            v.extend(item.into_bytes());
-            v
-        });
+    let command: Vec<_> = if let Some(mut term) = term.filter(|t| !t.is_empty()) {
+        // Parts are quoted, as the terminal passes the command to the shell.
+        let parts: Vec<_> = command_iter.collect();
+        let mut command = shlex::bytes::try_join(parts.iter().map(|p| p.as_bytes()))
+            .context("invalid command")?;
         if let Some(args) = args {
             command.push(b' ');
             command.extend(args.as_bytes());
         }
 
-        term.push(CString::new(command).expect("invalid command"));
+        term.push(CString::new(command).context("invalid command arguments")?);
         term
     } else {
         let args_iter = args.iter().flat_map(|args| {
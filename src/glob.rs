@@ -0,0 +1,32 @@
+/// Matches `s` against shell-like `pattern` with `*` and `?` wildcards.
+pub fn matches(pattern: &str, s: &str) -> bool {
+    let mut pattern = pattern.chars();
+    match pattern.next() {
+        None => s.is_empty(),
+        Some('*') => s
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([s.len()])
+            .any(|i| matches(pattern.as_str(), &s[i..])),
+        Some(c) => {
+            let mut s = s.chars();
+            s.next().is_some_and(|sc| c == '?' || c == sc) && matches(pattern.as_str(), s.as_str())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("*", "anything", true)]
+    #[test_case("*.conf", "a.conf", true)]
+    #[test_case("*.conf", "a.config", false)]
+    #[test_case("host?", "host1", true)]
+    #[test_case("host?", "host", false)]
+    fn test_matches(pattern: &str, s: &str, expected: bool) {
+        assert_eq!(matches(pattern, s), expected);
+    }
+}
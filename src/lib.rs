@@ -1,18 +1,24 @@
+pub use chord::Chord;
 pub(crate) use color::Color;
 pub(crate) use desktop::Entry as DesktopEntry;
 pub(crate) use draw::DrawTarget;
 
+mod calc;
+mod chord;
 mod color;
 mod draw;
 mod exec;
 mod font;
+mod glob;
 mod icon;
 mod input_parser;
 mod style;
+mod uri;
 mod usage_cache;
 
 pub mod config;
 pub mod desktop;
+pub mod mime;
 pub mod mode;
 pub mod state;
 pub mod window;
@@ -1,10 +1,12 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use log::LevelFilter;
 
-use yofi::{config, desktop, mode, prog_name, state, window};
+use yofi::mode::{self, Mode};
+use yofi::{config, desktop, mime, prog_name, state, window};
 
 fn setup_logger(level: LevelFilter, args: &Args) -> Result<()> {
     let dispatcher = fern::Dispatch::new()
@@ -86,6 +88,21 @@ enum ModeArg {
     Apps(AppsMode),
     Binapps(BinappsMode),
     Dialog(DialogMode),
+    Script(ScriptMode),
+    Combi(CombiMode),
+    Calc(CalcMode),
+    Ssh(SshMode),
+    Chars(CharsMode),
+    Files(FilesMode),
+    OpenWith(OpenWithMode),
+    Menu(MenuMode),
+    Pass(PassMode),
+    Bookmarks(BookmarksMode),
+    Recent(RecentMode),
+    Procs(ProcsMode),
+    Windows(WindowsMode),
+    Toplevels(ToplevelsMode),
+    Man(ManMode),
 }
 
 /// Desktop apps mode
@@ -107,8 +124,240 @@ struct BinappsMode {}
 
 /// Dialog mode
 #[derive(FromArgs)]
-#[argh(subcommand, name = "dialog")]
-struct DialogMode {}
+#[argh(
+    subcommand,
+    name = "dialog",
+    note = "With `--multi` lines are marked with Ctrl+Space and all the marked ones \
+            are printed in the marking order. Fields are selected by comma-separated \
+            1-based indices and ranges, e.g. `1`, `-1` (the last one), `2..` or `1..3`. \
+            Alt+1..Alt+9 accept the selection exiting with 10..18, more keys are bound \
+            with `--bind <chord>=<exit code>`, e.g. `--bind ctrl+Delete=20`."
+)]
+struct DialogMode {
+    /// read lines from the file instead of stdin.
+    #[argh(option)]
+    input: Option<PathBuf>,
+    /// allow selecting multiple lines.
+    #[argh(switch)]
+    multi: bool,
+    /// read NUL-separated records, which may span several lines, and print
+    /// them NUL-terminated.
+    #[argh(switch, short = '0')]
+    null: bool,
+    /// read lines as JSON objects with `name`, `subname`, `icon`, `value` and
+    /// `actions` fields.
+    #[argh(switch)]
+    json: bool,
+    /// printed for the accepted line: text, index (zero-based, -1 for custom
+    /// input), query or json.
+    #[argh(option, default = "Default::default()")]
+    format: mode::DialogFormat,
+    /// reject the input matching no line.
+    #[argh(switch)]
+    no_custom: bool,
+    /// initial input.
+    #[argh(option)]
+    query: Option<String>,
+    /// line selected on start, or its zero-based index.
+    #[argh(option)]
+    select: Option<String>,
+    /// accept right away once a single line is left by the filter.
+    #[argh(switch)]
+    auto_select: bool,
+    /// field delimiter, tab by default.
+    #[argh(option, short = 'd', default = "\"\\t\".into()")]
+    delimiter: String,
+    /// fields displayed as the entry name.
+    #[argh(option)]
+    with_nth: Option<mode::DialogFields>,
+    /// fields searched by the input, the displayed ones by default.
+    #[argh(option)]
+    nth: Option<mode::DialogFields>,
+    /// fields printed once accepted, the whole line by default.
+    #[argh(option)]
+    accept_nth: Option<mode::DialogFields>,
+    /// fields displayed as the entry subname.
+    #[argh(option)]
+    subname_nth: Option<mode::DialogFields>,
+    /// key accepting the selection with the exit code, like `alt+d=12`.
+    #[argh(option, from_str_fn(parse_accept_key))]
+    bind: Vec<(yofi::Chord, i32)>,
+}
+
+fn parse_accept_key(s: &str) -> Result<(yofi::Chord, i32), String> {
+    let (chord, exit_code) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected `<chord>=<exit code>`, got `{s}`"))?;
+    let exit_code: u8 = exit_code
+        .parse()
+        .map_err(|e| format!("invalid exit code `{exit_code}`: {e}"))?;
+    // Reserved for the plain accept and cancel.
+    if exit_code == 0 || i32::from(exit_code) == mode::DIALOG_CANCEL_EXIT_CODE {
+        return Err(format!("exit code {exit_code} is reserved"));
+    }
+    Ok((chord.parse()?, exit_code.into()))
+}
+
+impl DialogMode {
+    fn options(&self) -> mode::DialogOptions {
+        mode::DialogOptions {
+            multi: self.multi,
+            json: self.json,
+            null: self.null,
+            format: self.format,
+            no_custom: self.no_custom,
+            select: self.select.clone(),
+            auto_select: self.auto_select,
+            delimiter: self.delimiter.clone(),
+            with_nth: self.with_nth.clone(),
+            nth: self.nth.clone(),
+            accept_nth: self.accept_nth.clone(),
+            subname_nth: self.subname_nth.clone(),
+            accept_keys: self.bind.clone(),
+        }
+    }
+}
+
+/// Script mode
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "script",
+    note = "Each line of the command output is an entry name optionally followed by \
+            `\\0key\\x1fvalue\\x1f...` options: `subname`, `icon` (theme name or path) \
+            and `info` (passed back instead of the name). Once an entry is selected \
+            the command is invoked again with it as an extra argument and yofi exits \
+            if nothing is printed."
+)]
+struct ScriptMode {
+    /// command printing the entries
+    #[argh(positional)]
+    command: String,
+}
+
+/// Combined apps, binaries and optionally dialog mode
+#[derive(FromArgs)]
+#[argh(subcommand, name = "combi")]
+struct CombiMode {
+    /// optional path to ignored desktop files.
+    #[argh(option)]
+    blacklist: Option<PathBuf>,
+    /// flag for including lines from stdin as in dialog mode.
+    #[argh(switch)]
+    dialog: bool,
+}
+
+/// Calculator mode
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "calc",
+    note = "Evaluates arithmetic with functions (e.g. `sqrt`, `sin`, `max`), \
+            `0x`/`0o`/`0b` literals and unit conversions like `5 km to mi` or \
+            `255 to hex`. Other modes evaluate input starting with `=` the same way."
+)]
+struct CalcMode {}
+
+/// SSH hosts mode
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "ssh",
+    note = "Hosts are taken from `~/.ssh/config` (including `Include`d files) and \
+            `~/.ssh/known_hosts`. Hashed known hosts (see `HashKnownHosts` in \
+            ssh_config(5)) are listed only if their names are found in ssh config or \
+            among plain known hosts."
+)]
+struct SshMode {}
+
+/// Emoji and Unicode characters mode
+#[derive(FromArgs)]
+#[argh(subcommand, name = "chars")]
+struct CharsMode {}
+
+/// File browser mode
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "files",
+    note = "Selecting a directory enters it and Backspace on empty input goes up. \
+            Typing `~dir` and pressing Enter jumps to the directory (relative to the \
+            current one), `~` alone jumps to the home directory."
+)]
+struct FilesMode {
+    /// starting directory, the current one by default
+    #[argh(positional)]
+    dir: Option<PathBuf>,
+    /// flag for showing hidden files.
+    #[argh(switch)]
+    hidden: bool,
+    /// flag for printing the selected file path instead of opening it.
+    #[argh(switch)]
+    print: bool,
+}
+
+/// Applications able to open the file
+#[derive(FromArgs)]
+#[argh(subcommand, name = "open-with")]
+struct OpenWithMode {
+    /// file to open
+    #[argh(positional)]
+    file: PathBuf,
+}
+
+/// Menu from the config
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "menu",
+    note = "Menus are declared in the config as `[[menu.<name>]]` tables with `name`, \
+            `exec` and optional `subname`, `icon` and `terminal` keys."
+)]
+struct MenuMode {
+    /// menu name
+    #[argh(positional)]
+    name: String,
+}
+
+/// Password store entries mode
+#[derive(FromArgs)]
+#[argh(subcommand, name = "pass")]
+struct PassMode {
+    /// command run with the selected entry as the last argument (e.g. `pass -c`),
+    /// the entry is printed if not specified.
+    #[argh(option)]
+    command: Option<String>,
+}
+
+/// GTK and Chromium bookmarks mode
+#[derive(FromArgs)]
+#[argh(subcommand, name = "bookmarks")]
+struct BookmarksMode {}
+
+/// Recently used files mode
+#[derive(FromArgs)]
+#[argh(subcommand, name = "recent")]
+struct RecentMode {}
+
+/// User processes mode, sending a signal to the selected one
+#[derive(FromArgs)]
+#[argh(subcommand, name = "procs")]
+struct ProcsMode {}
+
+/// Sway/i3 windows mode, focusing the selected one
+#[derive(FromArgs)]
+#[argh(subcommand, name = "windows")]
+struct WindowsMode {}
+
+/// Windows mode for wlroots-based compositors, activating the selected one
+#[derive(FromArgs)]
+#[argh(subcommand, name = "toplevels")]
+struct ToplevelsMode {}
+
+/// Man pages mode, opening the selected page in terminal
+#[derive(FromArgs)]
+#[argh(subcommand, name = "man")]
+struct ManMode {}
 
 impl ModeArg {
     fn try_default() -> Result<Self> {
@@ -122,6 +371,271 @@ impl ModeArg {
             list: false,
         }))
     }
+
+    fn from_config(mode: &str) -> Result<Self> {
+        let args = shlex::split(mode).with_context(|| format!("invalid mode: {mode}"))?;
+        let (name, args) = args.split_first().context("empty mode")?;
+        let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+        ModeArg::from_args(&[prog_name!(), name], &args)
+            .map_err(|e| anyhow::anyhow!("invalid mode `{mode}`: {}", e.output))
+    }
+
+    fn has_icons(&self) -> bool {
+        !matches!(
+            self,
+            ModeArg::Binapps(_)
+                | ModeArg::Calc(_)
+                | ModeArg::Ssh(_)
+                | ModeArg::Chars(_)
+                | ModeArg::Pass(_)
+                | ModeArg::Procs(_)
+                | ModeArg::Man(_)
+        )
+    }
+
+    fn reads_stdin(&self) -> bool {
+        match self {
+            ModeArg::Dialog(DialogMode { input, .. }) => input.is_none(),
+            ModeArg::Combi(CombiMode { dialog, .. }) => *dialog,
+            _ => false,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            ModeArg::Apps(_) => "apps".into(),
+            ModeArg::Binapps(_) => "binapps".into(),
+            ModeArg::Dialog(_) => "dialog".into(),
+            ModeArg::Script(ScriptMode { command }) => shlex::split(command)
+                .and_then(|c| c.into_iter().next())
+                .and_then(|c| Some(Path::new(&c).file_name()?.to_str()?.to_owned()))
+                .unwrap_or_else(|| "script".into()),
+            ModeArg::Combi(_) => "combi".into(),
+            ModeArg::Calc(_) => "calc".into(),
+            ModeArg::Ssh(_) => "ssh".into(),
+            ModeArg::Chars(_) => "chars".into(),
+            ModeArg::Files(_) => "files".into(),
+            ModeArg::OpenWith(_) => "open-with".into(),
+            ModeArg::Menu(MenuMode { name }) => name.clone(),
+            ModeArg::Pass(_) => "pass".into(),
+            ModeArg::Bookmarks(_) => "bookmarks".into(),
+            ModeArg::Recent(_) => "recent".into(),
+            ModeArg::Procs(_) => "procs".into(),
+            ModeArg::Windows(_) => "windows".into(),
+            ModeArg::Toplevels(_) => "toplevels".into(),
+            ModeArg::Man(_) => "man".into(),
+        }
+    }
+}
+
+fn desktop_entries(
+    config: &config::Config,
+    blacklist: Option<&PathBuf>,
+) -> Result<Vec<desktop::Entry>> {
+    let blacklist_filter = blacklist
+        .and_then(|file| {
+            let entries = std::fs::read_to_string(file)
+                .map_err(|e| log::debug!("cannot read blacklist file {:?}: {}", file, e))
+                .ok()?
+                .lines()
+                .map(std::ffi::OsString::from)
+                .collect::<HashSet<_>>();
+
+            Some(Box::new(move |e: &_| !entries.contains(e)) as Box<dyn Fn(&_) -> bool>)
+        })
+        .unwrap_or_else(|| Box::new(|_| true));
+
+    Ok(desktop::Traverser::new(config.param(), blacklist_filter)
+        .context("cannot load desktop file traverser")?
+        .find_entries())
+}
+
+fn icon_lookup(config: &config::Config) -> Result<Option<desktop::IconLookup>> {
+    config
+        .param::<Option<desktop::IconConfig>>()
+        .map(desktop::IconLookup::new)
+        .transpose()
+        .context("cannot load icon lookup")
+}
+
+// For modes running commands only useful in terminal.
+fn required_terminal(config: &config::Config, mode: &str) -> Result<Vec<CString>> {
+    let term = config.terminal_command();
+    anyhow::ensure!(
+        !term.is_empty(),
+        "{mode} mode requires a terminal, set `term` config option"
+    );
+    Ok(term)
+}
+
+// Stdin is reopened rather than duplicated, as the window makes it
+// non-blocking, which would leak to the caller. Only pipes, sockets and
+// terminals are reopened to be polled, other files are read at once from the
+// caller's offset.
+fn read_stdin(lines: &mut window::LineBuffer) -> Result<()> {
+    use nix::sys::stat::{fstat, SFlag};
+    use std::os::fd::AsRawFd;
+
+    let pollable = fstat(std::io::stdin().as_raw_fd()).is_ok_and(|stat| {
+        let kind = SFlag::from_bits_truncate(stat.st_mode) & SFlag::S_IFMT;
+        [SFlag::S_IFIFO, SFlag::S_IFSOCK, SFlag::S_IFCHR].contains(&kind)
+    });
+    if pollable {
+        match std::fs::File::open("/proc/self/fd/0") {
+            Ok(stdin) => {
+                lines.set_input(stdin);
+                return Ok(());
+            }
+            Err(e) => log::debug!("reading stdin at once, cannot reopen it: {e}"),
+        }
+    }
+    lines
+        .read_all(std::io::stdin().lock())
+        .context("failed to read stdin")
+}
+
+// Lines of the file or stdin, read by the window as they arrive.
+fn dialog_input(
+    path: Option<&Path>,
+    separator: u8,
+    inputs: &mut Vec<window::InputLines>,
+) -> Result<window::InputLines> {
+    let mut lines = window::LineBuffer::new(separator);
+    match path {
+        Some(path) => lines.set_input(
+            std::fs::File::open(path).with_context(|| format!("cannot open {}", path.display()))?,
+        ),
+        None => read_stdin(&mut lines)?,
+    }
+    let lines = window::InputLines::new(lines.into());
+    inputs.push(lines.clone());
+    Ok(lines)
+}
+
+fn build_mode(
+    mode_arg: &ModeArg,
+    config: &config::Config,
+    toplevels: &window::Toplevels,
+    inputs: &mut Vec<window::InputLines>,
+) -> Result<Option<Box<dyn Mode>>> {
+    let mode: Box<dyn Mode> = match mode_arg {
+        ModeArg::Apps(AppsMode { blacklist, list }) => {
+            let entries = desktop_entries(config, blacklist.as_ref())?;
+
+            if *list {
+                for e in entries {
+                    println!("{}: {}", e.entry.name, e.desktop_fname);
+                }
+                return Ok(None);
+            }
+
+            Box::new(mode::AppsMode::new(entries, config.terminal_command()))
+        }
+        ModeArg::Binapps(BinappsMode {}) => {
+            Box::new(mode::BinsMode::new(config.terminal_command()))
+        }
+        ModeArg::Dialog(dialog) => {
+            let icon_lookup = if dialog.json {
+                icon_lookup(config)?
+            } else {
+                None
+            };
+            let options = dialog.options();
+            let input = dialog_input(dialog.input.as_deref(), options.separator(), inputs)?;
+            Box::new(mode::DialogMode::streaming(input, options, icon_lookup))
+        }
+        ModeArg::Script(ScriptMode { command }) => {
+            let command = shlex::split(command)
+                .with_context(|| format!("invalid script command: {command}"))?;
+            Box::new(mode::ScriptMode::new(command, icon_lookup(config)?)?)
+        }
+        ModeArg::Combi(CombiMode { blacklist, dialog }) => {
+            let entries = desktop_entries(config, blacklist.as_ref())?;
+            let mut sources: Vec<(_, Box<dyn Mode>)> = vec![
+                (
+                    "apps".to_owned(),
+                    Box::new(mode::AppsMode::new(entries, config.terminal_command())),
+                ),
+                (
+                    "bin".to_owned(),
+                    Box::new(mode::BinsMode::new(config.terminal_command())),
+                ),
+            ];
+            if *dialog {
+                sources.push((
+                    "dialog".to_owned(),
+                    Box::new(mode::DialogMode::streaming(
+                        dialog_input(None, b'\n', inputs)?,
+                        Default::default(),
+                        None,
+                    )),
+                ));
+            }
+            Box::new(mode::CombiMode::new(sources))
+        }
+        ModeArg::Calc(CalcMode {}) => Box::new(mode::CalcMode::new()),
+        ModeArg::Ssh(SshMode {}) => Box::new(mode::SshMode::new(required_terminal(config, "ssh")?)),
+        ModeArg::Chars(CharsMode {}) => Box::new(mode::CharsMode::new()),
+        ModeArg::Files(FilesMode { dir, hidden, print }) => Box::new(mode::FilesMode::new(
+            dir.clone().unwrap_or_else(|| ".".into()),
+            *hidden,
+            *print,
+            icon_lookup(config)?,
+        )?),
+        ModeArg::Menu(MenuMode { name }) => {
+            let entries = config
+                .menu(name)
+                .with_context(|| format!("menu `{name}` is not found in config"))?;
+            Box::new(mode::MenuMode::new(
+                name,
+                entries.to_vec(),
+                config.terminal_command(),
+                icon_lookup(config)?,
+            ))
+        }
+        ModeArg::Pass(PassMode { command }) => {
+            let command = command
+                .as_deref()
+                .map(|c| shlex::split(c).with_context(|| format!("invalid pass command: {c}")))
+                .transpose()?;
+            Box::new(mode::PassMode::new(command))
+        }
+        ModeArg::Bookmarks(BookmarksMode {}) => {
+            Box::new(mode::BookmarksMode::new(icon_lookup(config)?))
+        }
+        ModeArg::Recent(RecentMode {}) => Box::new(mode::RecentMode::new(
+            desktop_entries(config, None)?,
+            config.terminal_command(),
+        )),
+        ModeArg::Procs(ProcsMode {}) => Box::new(mode::ProcsMode::new()?),
+        ModeArg::Windows(WindowsMode {}) => {
+            Box::new(mode::WindowsMode::new(desktop_entries(config, None)?)?)
+        }
+        ModeArg::Toplevels(ToplevelsMode {}) => Box::new(mode::ToplevelsMode::new(
+            toplevels.clone(),
+            desktop_entries(config, None)?,
+        )),
+        ModeArg::Man(ManMode {}) => Box::new(mode::ManMode::new(required_terminal(config, "man")?)),
+        ModeArg::OpenWith(OpenWithMode { file }) => {
+            let mime_db = mime::MimeDb::load();
+            let mime = mime_db.guess(file).unwrap_or("application/octet-stream");
+            log::debug!("{} has {mime} type", file.display());
+
+            let file = file
+                .to_str()
+                .with_context(|| format!("non-UTF8 file path: {}", file.display()))?;
+            Box::new(mode::OpenWithMode::new(
+                file.to_owned(),
+                &mime_db.with_parents(mime),
+                desktop_entries(config, None)?,
+                &desktop::mimeapps::MimeApps::load(),
+                config.terminal_command(),
+            ))
+        }
+    };
+
+    Ok(Some(mode))
 }
 
 fn main_inner() -> Result<()> {
@@ -146,53 +660,58 @@ fn main_inner() -> Result<()> {
         config.override_password();
     }
 
-    let default_mode_arg;
-    let mode_arg = match args.mode.as_ref() {
-        Some(m) => m,
-        None => {
-            default_mode_arg = ModeArg::try_default()?;
-            &default_mode_arg
-        }
+    let mode_args = match args.mode.take() {
+        Some(m) => vec![m],
+        None if config.modes().is_empty() => vec![ModeArg::try_default()?],
+        None => config
+            .modes()
+            .iter()
+            .map(|m| ModeArg::from_config(m))
+            .collect::<Result<_>>()?,
     };
-    let cmd = match mode_arg {
-        ModeArg::Apps(AppsMode { blacklist, list }) => {
-            let blacklist_filter = blacklist
-                .as_ref()
-                .and_then(|file| {
-                    let entries = std::fs::read_to_string(file)
-                        .map_err(|e| log::debug!("cannot read blacklist file {:?}: {}", file, e))
-                        .ok()?
-                        .lines()
-                        .map(std::ffi::OsString::from)
-                        .collect::<HashSet<_>>();
-
-                    Some(Box::new(move |e: &_| !entries.contains(e)) as Box<dyn Fn(&_) -> bool>)
-                })
-                .unwrap_or_else(|| Box::new(|_| true));
-
-            let entries = desktop::Traverser::new(config.param(), blacklist_filter)
-                .context("cannot load desktop file traverser")?
-                .find_entries();
 
-            if *list {
-                for e in entries {
-                    println!("{}: {}", e.entry.name, e.desktop_fname);
-                }
-                return Ok(());
-            }
+    // No need to load icons if none of the modes shows them.
+    if !mode_args.iter().any(ModeArg::has_icons) {
+        config.disable_icons();
+    }
 
-            mode::Mode::apps(entries, config.terminal_command())
-        }
-        ModeArg::Binapps(BinappsMode {}) => {
-            config.disable_icons();
-            mode::Mode::bins(config.terminal_command())
+    // Lines would be split between the modes otherwise.
+    anyhow::ensure!(
+        mode_args.iter().filter(|m| m.reads_stdin()).count() <= 1,
+        "only one mode can read stdin, use `dialog --input <file>` for others"
+    );
+
+    // Filled by the window, only if listed by a mode.
+    let toplevels = window::Toplevels::default();
+    let mut inputs = vec![];
+    let mut modes = vec![];
+    for mode_arg in &mode_args {
+        match build_mode(mode_arg, &config, &toplevels, &mut inputs)? {
+            Some(mode) => modes.push((mode_arg.label(), mode)),
+            None => return Ok(()),
         }
-        ModeArg::Dialog(DialogMode {}) => mode::Mode::dialog()?,
-    };
+    }
 
-    let state = state::State::new(cmd);
+    let mut state = state::State::with_modes(modes);
+    // Dialog output must be one of its entries or the input, as formatted.
+    let dialog = mode_args.iter().any(|m| matches!(m, ModeArg::Dialog(_)));
+    if config.calc_prefix() && !dialog {
+        state.enable_calc_prefix();
+    }
+    // The input is shared by all modes.
+    let query = mode_args.iter().find_map(|m| match m {
+        ModeArg::Dialog(dialog) => dialog.query.as_deref(),
+        _ => None,
+    });
+    if let Some(query) = query {
+        state.append_to_input(query);
+    }
+    let toplevels = mode_args
+        .iter()
+        .any(|m| matches!(m, ModeArg::Toplevels(_)))
+        .then_some(toplevels);
     let (mut window, mut event_loop) =
-        window::Window::new(config, state).context("unable create a window")?;
+        window::Window::new(config, state, toplevels, inputs).context("unable create a window")?;
 
     while !window.asked_exit() {
         event_loop.dispatch(None, &mut window)?;
@@ -201,6 +720,10 @@ fn main_inner() -> Result<()> {
         }
     }
 
+    // Accepted entries exit from modes themselves, so it's cancelled.
+    if let Some(code) = window.cancel_exit_code() {
+        std::process::exit(code);
+    }
     Ok(())
 }
 
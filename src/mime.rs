@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const DIRECTORY: &str = "inode/directory";
+
+struct Glob {
+    weight: u32,
+    mime: String,
+    pattern: String,
+    case_sensitive: bool,
+}
+
+impl Glob {
+    fn matches(&self, fname: &str, lowercase_fname: &str) -> bool {
+        let fname = if self.case_sensitive {
+            fname
+        } else {
+            lowercase_fname
+        };
+
+        match self.pattern.strip_prefix('*') {
+            Some(suffix) if !suffix.contains(['*', '?', '[']) => fname.ends_with(suffix),
+            _ => crate::glob::matches(&self.pattern, fname),
+        }
+    }
+}
+
+/// Shared MIME-info database, see
+/// <https://specifications.freedesktop.org/shared-mime-info-spec/latest/>.
+/// Only file names are matched, contents are never sniffed.
+#[derive(Default)]
+pub struct MimeDb {
+    globs: Vec<Glob>,
+    icons: HashMap<String, String>,
+    generic_icons: HashMap<String, String>,
+    // MIME type to its parent types
+    subclasses: HashMap<String, Vec<String>>,
+}
+
+impl MimeDb {
+    pub fn load() -> Self {
+        let xdg_dirs = crate::desktop::xdg_dirs();
+        let mut db = Self::default();
+
+        // Files are listed from the most important directory.
+        for path in xdg_dirs.find_data_files("mime/globs2") {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => db.add_globs(&content),
+                Err(e) => log::warn!("cannot read {}: {e}", path.display()),
+            }
+        }
+
+        for (file, map) in [
+            ("mime/icons", &mut db.icons),
+            ("mime/generic-icons", &mut db.generic_icons),
+        ] {
+            for path in xdg_dirs.find_data_files(file) {
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                for (mime, icon) in content.lines().filter_map(|l| l.split_once(':')) {
+                    map.entry(mime.to_owned())
+                        .or_insert_with(|| icon.to_owned());
+                }
+            }
+        }
+
+        for path in xdg_dirs.find_data_files("mime/subclasses") {
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                db.add_subclasses(&content);
+            }
+        }
+
+        db
+    }
+
+    // Lines are `weight:mime:pattern[:flags]`.
+    fn add_globs(&mut self, content: &str) {
+        for line in content.lines().filter(|l| !l.starts_with('#')) {
+            let mut fields = line.split(':');
+            let (Some(weight), Some(mime), Some(pattern)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(weight) = weight.parse() else {
+                continue;
+            };
+            let case_sensitive = fields
+                .next()
+                .is_some_and(|f| f.split(',').any(|f| f == "cs"));
+
+            self.globs.push(Glob {
+                weight,
+                mime: mime.to_owned(),
+                pattern: if case_sensitive {
+                    pattern.to_owned()
+                } else {
+                    pattern.to_lowercase()
+                },
+                case_sensitive,
+            });
+        }
+    }
+
+    // Lines are `mime parent`.
+    fn add_subclasses(&mut self, content: &str) {
+        for (mime, parent) in content.lines().filter_map(|l| l.split_once(' ')) {
+            let parents = self.subclasses.entry(mime.to_owned()).or_default();
+            if !parents.iter().any(|p| p == parent) {
+                parents.push(parent.to_owned());
+            }
+        }
+    }
+
+    /// The MIME type followed by all its ancestors, breadth first.
+    pub fn with_parents<'a>(&'a self, mime: &'a str) -> Vec<&'a str> {
+        let mut types = vec![mime];
+        let mut i = 0;
+        while let Some(&mime) = types.get(i) {
+            let parents = self.subclasses.get(mime).into_iter().flatten();
+            // All text files are also plain text ones.
+            let implicit =
+                (mime.starts_with("text/") && mime != "text/plain").then_some("text/plain");
+
+            for parent in parents.map(String::as_str).chain(implicit) {
+                if !types.contains(&parent) {
+                    types.push(parent);
+                }
+            }
+            i += 1;
+        }
+        types
+    }
+
+    /// Guesses MIME type by the file name, directories are `inode/directory`.
+    pub fn guess(&self, path: &Path) -> Option<&str> {
+        if path.is_dir() {
+            return Some(DIRECTORY);
+        }
+
+        let fname = path.file_name()?.to_string_lossy();
+        let lowercase = fname.to_lowercase();
+
+        // The heaviest and then the longest pattern wins.
+        self.globs
+            .iter()
+            .filter(|g| g.matches(&fname, &lowercase))
+            .max_by_key(|g| (g.weight, g.pattern.len()))
+            .map(|g| g.mime.as_str())
+    }
+
+    /// Icon names for the MIME type from the most specific one.
+    pub fn icon_names(&self, mime: &str) -> Vec<String> {
+        let media = mime.split('/').next().unwrap_or(mime);
+
+        self.icons
+            .get(mime)
+            .cloned()
+            .into_iter()
+            .chain([mime.replace('/', "-")])
+            .chain(self.generic_icons.get(mime).cloned())
+            .chain([format!("{media}-x-generic")])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    const GLOBS: &str = "\
+# comment
+50:text/x-csrc:*.c
+50:text/x-c++src:*.C:cs
+50:text/x-c++src:*.cpp
+50:application/gzip:*.gz
+50:application/x-compressed-tar:*.tar.gz
+10:text/x-readme:readme*
+50:text/x-makefile:makefile
+";
+
+    #[test_case("main.c", Some("text/x-csrc"); "suffix")]
+    #[test_case("MAIN.C", Some("text/x-c++src"); "case sensitive")]
+    #[test_case("Main.Cpp", Some("text/x-c++src"); "case insensitive")]
+    #[test_case("a.tar.gz", Some("application/x-compressed-tar"); "longest pattern")]
+    #[test_case("a.gz", Some("application/gzip"); "shorter pattern")]
+    #[test_case("README.md", Some("text/x-readme"); "wildcard")]
+    #[test_case("Makefile", Some("text/x-makefile"); "literal")]
+    #[test_case("file.unknown", None; "unknown")]
+    fn test_guess(fname: &str, expected: Option<&str>) {
+        let mut db = MimeDb::default();
+        db.add_globs(GLOBS);
+        assert_eq!(db.guess(Path::new(fname)), expected);
+    }
+
+    #[test]
+    fn test_guess_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut db = MimeDb::default();
+        db.add_globs(GLOBS);
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"\xff.c"));
+        assert_eq!(db.guess(path), Some("text/x-csrc"));
+    }
+
+    #[test]
+    fn test_with_parents() {
+        let mut db = MimeDb::default();
+        db.add_subclasses(
+            "application/x-shellscript application/x-executable\n\
+             application/x-shellscript text/plain\n\
+             text/x-csrc text/x-c\n",
+        );
+
+        assert_eq!(
+            db.with_parents("application/x-shellscript"),
+            [
+                "application/x-shellscript",
+                "application/x-executable",
+                "text/plain"
+            ]
+        );
+        assert_eq!(
+            db.with_parents("text/x-csrc"),
+            ["text/x-csrc", "text/x-c", "text/plain"]
+        );
+    }
+
+    #[test]
+    fn test_icon_names() {
+        let mut db = MimeDb::default();
+        db.generic_icons
+            .insert("text/x-csrc".into(), "text-x-script".into());
+        assert_eq!(
+            db.icon_names("text/x-csrc"),
+            ["text-x-csrc", "text-x-script", "text-x-generic"]
+        );
+    }
+}
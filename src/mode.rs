@@ -1,42 +1,55 @@
-use std::ffi::CString;
-use std::iter::ExactSizeIterator;
-
 use anyhow::{Context, Result};
-use either::Either;
-use raqote::Image;
-
-use crate::input_parser::InputValue;
-use crate::DesktopEntry;
+pub use raqote::Image;
+
+pub use crate::input_parser::InputValue;
+use crate::Chord;
+pub use apps::AppsMode;
+pub use bins::BinsMode;
+pub use bookmarks::BookmarksMode;
+pub use calc::CalcMode;
+pub use chars::CharsMode;
+pub use combi::CombiMode;
+pub use dialog::{
+    DialogMode, DialogOptions, Fields as DialogFields, OutputFormat as DialogFormat,
+    CANCEL_EXIT_CODE as DIALOG_CANCEL_EXIT_CODE,
+};
+pub use files::FilesMode;
+pub use man::ManMode;
+pub use menu::MenuMode;
+pub use open_with::OpenWithMode;
+pub use pass::PassMode;
+pub use procs::ProcsMode;
+pub use recent::RecentMode;
+pub use script::ScriptMode;
+pub use ssh::SshMode;
+pub use toplevels::ToplevelsMode;
+pub use windows::WindowsMode;
 
 mod apps;
 mod bins;
+mod bookmarks;
+pub(crate) mod calc;
+mod chars;
+mod combi;
 mod dialog;
-
-macro_rules! delegate {
-    (pub fn $name:ident ( &mut self ) -> $ret:ty $(, wrap_with ($wrap:path))?) => {
-        delegate!(pub fn $name ( & [mut] self, ) -> $ret $(, wrap_with ($wrap))?);
-    };
-    (pub fn $name:ident ( &mut self, $($ident:ident : $tp:ty),* ) -> $ret:ty $(, wrap_with ($wrap:path))?) => {
-        delegate!(pub fn $name ( & [mut] self, $($ident : $tp),* ) -> $ret $(, wrap_with ($wrap))?);
-    };
-    (pub fn $name:ident ( & $([$m:ident])? self ) -> $ret:ty $(, wrap_with ($wrap:path))?) => {
-        delegate!(pub fn $name ( & $([$m])? self, ) -> $ret $(, wrap_with ($wrap))?);
-    };
-    (pub fn $name:ident ( & $([$m:ident])? self, $($ident:ident : $tp:ty),* ) -> $ret:ty $(, wrap_with ($wrap:path))?) => {
-        pub fn $name ( & $($m)? self, $($ident : $tp),* ) -> $ret {
-            match self {
-                Mode::Apps(mode) => $($wrap)?(mode.$name($($ident),*)),
-                Mode::BinApps(mode) => $($wrap)?(mode.$name($($ident),*)),
-                Mode::Dialog(mode) => $($wrap)?(mode.$name($($ident),*)),
-            }
-        }
-    }
-}
+mod files;
+mod man;
+mod menu;
+mod open_with;
+mod pass;
+mod procs;
+mod recent;
+mod script;
+mod ssh;
+mod toplevels;
+mod windows;
 
 pub struct EvalInfo<'a> {
     pub index: Option<usize>,
     pub subindex: usize,
     pub input_value: &'a InputValue<'a>,
+    /// Set if accepted with one of `Mode::accept_keys`.
+    pub exit_code: Option<i32>,
 }
 
 impl<'a> std::ops::Deref for EvalInfo<'a> {
@@ -47,64 +60,117 @@ impl<'a> std::ops::Deref for EvalInfo<'a> {
     }
 }
 
-pub enum Mode {
-    Apps(apps::AppsMode),
-    BinApps(bins::BinsMode),
-    Dialog(dialog::DialogMode),
-}
-
 pub struct Entry<'a> {
     pub name: &'a str,
     pub subname: Option<&'a str>,
     pub icon: Option<Image<'a>>,
 }
 
-impl Mode {
-    pub fn apps(entries: Vec<DesktopEntry>, term: Vec<CString>) -> Self {
-        Self::Apps(apps::AppsMode::new(entries, term))
+/// Source of the menu entries and the action on the selected one.
+pub trait Mode {
+    /// Evaluates the selected entry. Returns only if the mode keeps running
+    /// afterwards (e.g. script mode got a new list of entries), otherwise the
+    /// process is replaced or exits.
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()>;
+
+    fn entries_len(&self) -> usize;
+
+    /// Number of subentries (e.g. desktop actions) of the entry, which are
+    /// selected with `subidx` starting from 1.
+    fn subentries_len(&self, idx: usize) -> usize;
+
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_>;
+
+    /// Texts of all entries used for the search, in the entries order.
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_>;
+
+    fn has_subname(&self, idx: usize) -> bool {
+        self.subentries_len(idx) > 0
     }
 
-    pub fn bins(term: Vec<CString>) -> Self {
-        Self::BinApps(bins::BinsMode::new(term))
+    /// Whether matches are highlighted in entry names, i.e. text entries start
+    /// with the displayed names.
+    fn highlights_matches(&self) -> bool {
+        true
     }
 
-    pub fn dialog() -> Result<Self> {
-        dialog::DialogMode::new().map(Self::Dialog)
+    /// Called on every input change, for modes whose entries depend on it.
+    fn update_input(&mut self, _input: &InputValue<'_>) {}
+
+    /// Whether entries are fuzzy-filtered by the input, otherwise they are
+    /// shown as is.
+    fn is_searchable(&self) -> bool {
+        true
     }
 
-    pub fn dialog_from_lines(lines: Vec<String>) -> Self {
-        Self::Dialog(dialog::DialogMode::from_lines(lines))
+    /// Toggles the entry mark for multiple selection. Returns whether marks
+    /// are supported.
+    fn toggle_mark(&mut self, _idx: usize) -> bool {
+        false
     }
 
-    pub fn fork_eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
-        // Safety:
-        // - no need for signal-safety as we single-thread everywhere;
-        // - all file descriptors are closed;
-        let pid = unsafe { nix::unistd::fork() }.context("fork() error")?;
+    fn is_marked(&self, _idx: usize) -> bool {
+        false
+    }
 
-        if pid.is_child() {
-            use std::os::fd::AsRawFd;
-            // Just in case, not sure it will break anything.
-            let _ = nix::unistd::close(std::io::stdin().as_raw_fd());
-            let _ = nix::unistd::close(std::io::stdout().as_raw_fd());
-            let _ = nix::unistd::close(std::io::stderr().as_raw_fd());
+    /// Called before entries are drawn, for modes whose entries change in the
+    /// background (e.g. on Wayland events).
+    fn refresh(&mut self) {}
 
-            self.eval(info)?;
-        }
+    /// Whether entries are still being appended, e.g. read from stdin.
+    fn is_loading(&self) -> bool {
+        false
+    }
+
+    /// Entry to select once it's appeared, e.g. the previous choice.
+    fn take_preselected(&mut self) -> Option<usize> {
+        None
+    }
+
+    /// Whether the input matching no entry is evaluated.
+    fn accepts_custom_input(&self) -> bool {
+        true
+    }
+
+    /// Whether the only entry left by the filter is accepted right away.
+    fn auto_accepts(&self) -> bool {
+        false
+    }
 
-        Ok(())
+    /// Extra keys accepting the selection, each with its process exit code.
+    fn accept_keys(&self) -> &[(Chord, i32)] {
+        &[]
     }
 
-    delegate!(pub fn eval(&mut self, info: EvalInfo<'_>) -> Result<std::convert::Infallible>);
-    delegate!(pub fn entries_len(&self) -> usize);
-    delegate!(pub fn subentries_len(&self, idx: usize) -> usize);
-    delegate!(pub fn entry(&self, idx: usize, subidx: usize) -> Entry<'_>);
-
-    pub fn text_entries(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
-        match self {
-            Mode::Apps(mode) => Either::Left(Either::Right(mode.text_entries())),
-            Mode::BinApps(mode) => Either::Left(Either::Left(mode.text_entries())),
-            Mode::Dialog(mode) => Either::Right(mode.text_entries()),
-        }
+    /// Process exit status once cancelled (e.g. with Escape), successful
+    /// if not set.
+    fn cancel_exit_code(&self) -> Option<i32> {
+        None
     }
+
+    /// Called on Backspace with empty input, e.g. to go up a directory.
+    /// Returns whether entries are changed.
+    fn back(&mut self) -> bool {
+        false
+    }
+}
+
+pub(crate) fn fork_eval(mode: &mut dyn Mode, info: EvalInfo<'_>) -> Result<()> {
+    // Safety:
+    // - no need for signal-safety as we single-thread everywhere;
+    // - all file descriptors are closed;
+    let pid = unsafe { nix::unistd::fork() }.context("fork() error")?;
+
+    if pid.is_child() {
+        use std::os::fd::AsRawFd;
+        // Just in case, not sure it will break anything.
+        let _ = nix::unistd::close(std::io::stdin().as_raw_fd());
+        let _ = nix::unistd::close(std::io::stdout().as_raw_fd());
+        let _ = nix::unistd::close(std::io::stderr().as_raw_fd());
+
+        mode.eval(info)?;
+        std::process::exit(0);
+    }
+
+    Ok(())
 }
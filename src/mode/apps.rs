@@ -3,7 +3,7 @@ use std::ffi::CString;
 
 use anyhow::{Context, Result};
 
-use super::{Entry, EvalInfo};
+use super::{Entry, EvalInfo, Mode};
 use crate::usage_cache::Usage;
 use crate::DesktopEntry;
 
@@ -27,8 +27,10 @@ impl AppsMode {
             usage,
         }
     }
+}
 
-    pub fn eval(&mut self, info: EvalInfo<'_>) -> Result<std::convert::Infallible> {
+impl Mode for AppsMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
         let idx = info.index.context("no app remain to launch")?;
         let entry = &self.entries[idx];
         let exec = if info.subindex == 0 {
@@ -37,10 +39,8 @@ impl AppsMode {
             &entry.actions[info.subindex - 1].exec
         };
 
-        let args = shlex::split(exec)
-            .with_context(|| format!("invalid app command line: {exec}"))?
+        let args = crate::desktop::expand_exec(exec, &[])?
             .into_iter()
-            .filter(|s| !s.starts_with('%')) // TODO: use placeholders somehow
             .map(|s| CString::new(s).expect("invalid argument"));
 
         self.usage
@@ -53,18 +53,18 @@ impl AppsMode {
             None
         };
 
-        crate::exec::exec(term, args, info.input_value)
+        crate::exec::exec(term, args, info.input_value).map(|x| match x {})
     }
 
-    pub fn entries_len(&self) -> usize {
+    fn entries_len(&self) -> usize {
         self.entries.len()
     }
 
-    pub fn subentries_len(&self, idx: usize) -> usize {
+    fn subentries_len(&self, idx: usize) -> usize {
         self.entries.get(idx).map(|e| e.actions.len()).unwrap_or(0)
     }
 
-    pub fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
         let entry = &self.entries[idx];
 
         Entry {
@@ -74,7 +74,7 @@ impl AppsMode {
         }
     }
 
-    pub fn text_entries(&self) -> impl super::ExactSizeIterator<Item = &str> {
-        self.entries.iter().map(|e| e.name.as_str())
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.entries.iter().map(|e| e.name.as_str()))
     }
 }
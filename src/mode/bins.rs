@@ -5,7 +5,7 @@ use std::os::unix::fs::PermissionsExt;
 
 use anyhow::{Context, Result};
 
-use super::{Entry, EvalInfo};
+use super::{Entry, EvalInfo, Mode};
 use crate::usage_cache::Usage;
 
 const CACHE_PATH: &str = concat!(crate::prog_name!(), ".bincache");
@@ -88,8 +88,10 @@ impl BinsMode {
             usage,
         }
     }
+}
 
-    pub fn eval(&mut self, info: EvalInfo<'_>) -> Result<std::convert::Infallible> {
+impl Mode for BinsMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
         let binary = if let Some(idx) = info.index {
             self.bins[idx].path.as_str()
         } else {
@@ -104,17 +106,18 @@ impl BinsMode {
             std::iter::once(CString::new(binary).context("invalid binary name")?),
             info.input_value,
         )
+        .map(|x| match x {})
     }
 
-    pub fn entries_len(&self) -> usize {
+    fn entries_len(&self) -> usize {
         self.bins.len()
     }
 
-    pub fn subentries_len(&self, _: usize) -> usize {
+    fn subentries_len(&self, _: usize) -> usize {
         0
     }
 
-    pub fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
         let bin = &self.bins[idx];
 
         let name = if let Some(name) = self.entry_name_cache.get(&bin.path) {
@@ -130,7 +133,7 @@ impl BinsMode {
         }
     }
 
-    pub fn text_entries(&self) -> impl super::ExactSizeIterator<Item = &str> {
-        self.bins.iter().map(|e| e.fname.as_str())
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.bins.iter().map(|e| e.fname.as_str()))
     }
 }
@@ -0,0 +1,273 @@
+use std::ffi::CString;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{Entry, EvalInfo, Mode};
+use crate::desktop::IconLookup;
+use crate::icon::Icon;
+
+// Config directories of Chromium-based browsers.
+const CHROMIUM_DIRS: &[&str] = &[
+    "chromium",
+    "google-chrome",
+    "google-chrome-beta",
+    "google-chrome-unstable",
+    "BraveSoftware/Brave-Browser",
+    "vivaldi",
+    "microsoft-edge",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+struct Bookmark {
+    name: String,
+    url: String,
+}
+
+impl Bookmark {
+    fn is_file(&self) -> bool {
+        self.url.starts_with("file://")
+    }
+}
+
+// Lines are `url [label]`, local directories are named by default.
+fn parse_gtk_line(line: &str) -> Option<Bookmark> {
+    let (url, label) = match line.trim().split_once(' ') {
+        Some((url, label)) => (url, Some(label)),
+        None => (line.trim(), None),
+    };
+    if url.is_empty() {
+        return None;
+    }
+
+    let name = match label {
+        Some(label) => label.to_owned(),
+        None => crate::uri::percent_decode(url.trim_end_matches('/').rsplit('/').next()?),
+    };
+
+    Some(Bookmark {
+        name,
+        url: url.to_owned(),
+    })
+}
+
+#[derive(Deserialize)]
+struct ChromiumBookmarks {
+    roots: std::collections::HashMap<String, ChromiumNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ChromiumNode {
+    Url {
+        name: String,
+        url: String,
+    },
+    Folder {
+        #[serde(default)]
+        children: Vec<ChromiumNode>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+fn flatten_chromium(node: ChromiumNode, bookmarks: &mut Vec<Bookmark>) {
+    match node {
+        ChromiumNode::Url { name, url } => bookmarks.push(Bookmark { name, url }),
+        ChromiumNode::Folder { children } => {
+            for child in children {
+                flatten_chromium(child, bookmarks);
+            }
+        }
+        ChromiumNode::Other => {}
+    }
+}
+
+fn parse_chromium(content: &str) -> Result<Vec<Bookmark>> {
+    let mut roots: Vec<_> = serde_json::from_str::<ChromiumBookmarks>(content)?
+        .roots
+        .into_iter()
+        .collect();
+    // Keep the bookmark bar first, then other and synced ones.
+    roots.sort_by(|(x, _), (y, _)| x.cmp(y));
+
+    let mut bookmarks = vec![];
+    for (_, node) in roots {
+        flatten_chromium(node, &mut bookmarks);
+    }
+    Ok(bookmarks)
+}
+
+fn read_gtk(config_home: &Path) -> Vec<Bookmark> {
+    let path = config_home.join("gtk-3.0/bookmarks");
+    match std::fs::read_to_string(&path) {
+        Ok(content) => content.lines().filter_map(parse_gtk_line).collect(),
+        Err(e) => {
+            log::debug!("cannot read {}: {e}", path.display());
+            vec![]
+        }
+    }
+}
+
+fn read_chromium(config_home: &Path) -> Vec<Bookmark> {
+    let profiles = CHROMIUM_DIRS
+        .iter()
+        .flat_map(|dir| {
+            std::fs::read_dir(config_home.join(dir))
+                .into_iter()
+                .flatten()
+        })
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name();
+            name == "Default" || name.to_string_lossy().starts_with("Profile ")
+        });
+
+    let mut bookmarks = vec![];
+    for profile in profiles {
+        let path = profile.path().join("Bookmarks");
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        match parse_chromium(&content) {
+            Ok(b) => bookmarks.extend(b),
+            Err(e) => log::warn!("invalid bookmarks file {}: {e}", path.display()),
+        }
+    }
+    bookmarks
+}
+
+pub struct BookmarksMode {
+    bookmarks: Vec<Bookmark>,
+    // icons of local and web bookmarks
+    file_icon: Option<Icon>,
+    web_icon: Option<Icon>,
+}
+
+impl BookmarksMode {
+    pub fn new(icon_lookup: Option<IconLookup>) -> Self {
+        let config_home = crate::desktop::xdg_dirs().get_config_home();
+
+        let mut bookmarks = read_gtk(&config_home);
+        bookmarks.extend(read_chromium(&config_home));
+        let mut seen = std::collections::HashSet::new();
+        bookmarks.retain(|b| seen.insert(b.url.clone()));
+
+        let find_icon = |name| icon_lookup.as_ref()?.find_icon(name);
+
+        Self {
+            bookmarks,
+            file_icon: find_icon("folder"),
+            web_icon: find_icon("text-html"),
+        }
+    }
+}
+
+impl Mode for BookmarksMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let url = info
+            .index
+            .map(|idx| self.bookmarks[idx].url.as_str())
+            .unwrap_or(info.input_value.search_string);
+
+        let args = ["xdg-open", url]
+            .into_iter()
+            .map(CString::new)
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid bookmark url")?;
+
+        crate::exec::exec(None, args, info.input_value).map(|x| match x {})
+    }
+
+    fn entries_len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, _: usize) -> bool {
+        true
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let bookmark = &self.bookmarks[idx];
+        let icon = if bookmark.is_file() {
+            &self.file_icon
+        } else {
+            &self.web_icon
+        };
+
+        Entry {
+            name: bookmark.name.as_str(),
+            subname: Some(bookmark.url.as_str()),
+            icon: icon.as_ref().and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.bookmarks.iter().map(|b| b.name.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("file:///home/user/Projects", Some(("Projects", "file:///home/user/Projects")); "directory")]
+    #[test_case("file:///home/user/My%20Files/", Some(("My Files", "file:///home/user/My%20Files/")); "percent encoded")]
+    #[test_case("sftp://host/srv Server files", Some(("Server files", "sftp://host/srv")); "with label")]
+    #[test_case("", None; "empty")]
+    fn test_parse_gtk_line(line: &str, expected: Option<(&str, &str)>) {
+        assert_eq!(
+            parse_gtk_line(line),
+            expected.map(|(name, url)| Bookmark {
+                name: name.into(),
+                url: url.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_chromium() {
+        let content = r#"{
+            "checksum": "abc",
+            "roots": {
+                "bookmark_bar": {
+                    "children": [
+                        { "name": "Rust", "type": "url", "url": "https://rust-lang.org/" },
+                        {
+                            "name": "Docs",
+                            "type": "folder",
+                            "children": [
+                                { "name": "std", "type": "url", "url": "https://doc.rust-lang.org/std/" }
+                            ]
+                        }
+                    ],
+                    "name": "Bookmarks bar",
+                    "type": "folder"
+                },
+                "other": { "children": [], "name": "Other bookmarks", "type": "folder" }
+            },
+            "version": 1
+        }"#;
+
+        assert_eq!(
+            parse_chromium(content).unwrap(),
+            [
+                Bookmark {
+                    name: "Rust".into(),
+                    url: "https://rust-lang.org/".into(),
+                },
+                Bookmark {
+                    name: "std".into(),
+                    url: "https://doc.rust-lang.org/std/".into(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+
+use super::{Entry, EvalInfo, InputValue, Mode};
+
+/// Prefix of the input evaluated by the calculator in other modes.
+pub const PREFIX: char = '=';
+
+pub struct CalcMode {
+    expr: String,
+    result: Option<Result<String, String>>,
+}
+
+impl CalcMode {
+    pub fn new() -> Self {
+        Self {
+            expr: String::new(),
+            result: None,
+        }
+    }
+
+    /// Whether the input is evaluated to a value, so there is something to print.
+    pub fn has_value(&self) -> bool {
+        matches!(self.result, Some(Ok(_)))
+    }
+}
+
+impl Default for CalcMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mode for CalcMode {
+    fn eval(&mut self, _info: EvalInfo<'_>) -> Result<()> {
+        match &self.result {
+            Some(Ok(value)) => {
+                println!("{value}");
+                std::process::exit(0);
+            }
+            // Invalid or incomplete expression is likely being typed.
+            Some(Err(_)) | None => Ok(()),
+        }
+    }
+
+    fn entries_len(&self) -> usize {
+        usize::from(self.result.is_some())
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn entry(&self, _: usize, _: usize) -> Entry<'_> {
+        let (name, subname) = match &self.result {
+            Some(Ok(value)) => (value.as_str(), None),
+            Some(Err(err)) => (self.expr.as_str(), Some(err.as_str())),
+            None => panic!("no calculator result"),
+        };
+
+        Entry {
+            name,
+            subname,
+            icon: None,
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new((0..self.entries_len()).map(|idx| self.entry(idx, 0).name))
+    }
+
+    fn has_subname(&self, _: usize) -> bool {
+        matches!(self.result, Some(Err(_)))
+    }
+
+    fn update_input(&mut self, input: &InputValue<'_>) {
+        let expr = input.source.strip_prefix(PREFIX).unwrap_or(input.source);
+        self.result = crate::calc::evaluate(expr);
+        self.expr = expr.trim().to_owned();
+    }
+
+    fn is_searchable(&self) -> bool {
+        false
+    }
+}
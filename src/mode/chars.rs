@@ -0,0 +1,148 @@
+use anyhow::Result;
+
+use super::{Entry, EvalInfo, Mode};
+
+const TABLE: &str = include_str!("chars.tsv");
+
+const VARIATION_SELECTOR: char = '\u{FE0F}';
+
+// Fitzpatrick modifiers, selected as subitems after the default one.
+const SKIN_TONES: [(char, &str); 5] = [
+    ('\u{1F3FB}', "light skin tone"),
+    ('\u{1F3FC}', "medium-light skin tone"),
+    ('\u{1F3FD}', "medium skin tone"),
+    ('\u{1F3FE}', "medium-dark skin tone"),
+    ('\u{1F3FF}', "dark skin tone"),
+];
+
+struct Char {
+    // the character itself followed by its skin tone variants, if any
+    variants: Vec<String>,
+    // variants prefixed to the name, as displayed
+    names: Vec<String>,
+    // displayed name and keywords
+    search: String,
+}
+
+// Modifier goes right after the base emoji and replaces emoji presentation
+// selector, e.g. `✌️` becomes `✌🏽`.
+fn with_tone(ch: &str, tone: char) -> String {
+    let mut chars = ch.chars();
+    let base = chars.next().unwrap_or_default();
+    let rest = chars.as_str().trim_start_matches(VARIATION_SELECTOR);
+    format!("{base}{tone}{rest}")
+}
+
+fn parse_table(table: &str) -> Vec<Char> {
+    table
+        .lines()
+        // Unlike comments, `#️⃣` keycap has no space after `#`.
+        .filter(|l| !l.is_empty() && !l.starts_with("# "))
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let (Some(ch), Some(name), keywords) = (fields.next(), fields.next(), fields.next())
+            else {
+                log::warn!("invalid chars table line: {line}");
+                return None;
+            };
+
+            let mut variants = vec![ch.to_owned()];
+            if fields.next() == Some("tone") {
+                variants.extend(SKIN_TONES.iter().map(|&(tone, _)| with_tone(ch, tone)));
+            }
+
+            let names: Vec<_> = variants.iter().map(|v| format!("{v}  {name}")).collect();
+            Some(Char {
+                // Starts with the displayed name, so matches are highlighted in it.
+                search: format!("{} {}", names[0], keywords.unwrap_or_default()),
+                names,
+                variants,
+            })
+        })
+        .collect()
+}
+
+pub struct CharsMode {
+    chars: Vec<Char>,
+}
+
+impl CharsMode {
+    pub fn new() -> Self {
+        Self {
+            chars: parse_table(TABLE),
+        }
+    }
+}
+
+impl Default for CharsMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mode for CharsMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let value = info
+            .index
+            .and_then(|idx| Some(self.chars[idx].variants.get(info.subindex)?.as_str()))
+            .unwrap_or(info.input_value.source);
+        println!("{value}");
+        std::process::exit(0);
+    }
+
+    fn entries_len(&self) -> usize {
+        self.chars.len()
+    }
+
+    fn subentries_len(&self, idx: usize) -> usize {
+        self.chars[idx].variants.len() - 1
+    }
+
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
+        let ch = &self.chars[idx];
+        let subname = match subidx {
+            _ if ch.variants.len() == 1 => None,
+            0 => Some("default skin tone"),
+            _ => Some(SKIN_TONES[subidx - 1].1),
+        };
+
+        Entry {
+            name: ch.names[subidx].as_str(),
+            subname,
+            icon: None,
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.chars.iter().map(|c| c.search.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("👋", '\u{1F3FD}', "👋🏽"; "single codepoint")]
+    #[test_case("✌\u{FE0F}", '\u{1F3FB}', "✌🏻"; "variation selector dropped")]
+    fn test_with_tone(ch: &str, tone: char, expected: &str) {
+        assert_eq!(with_tone(ch, tone), expected);
+    }
+
+    #[test]
+    fn test_table() {
+        let chars = parse_table(TABLE);
+        assert_eq!(
+            chars.len(),
+            TABLE.lines().filter(|l| !l.starts_with("# ")).count()
+        );
+
+        let wave = chars.iter().find(|c| c.variants[0] == "👋").unwrap();
+        assert_eq!(wave.variants.len(), 1 + SKIN_TONES.len());
+        assert_eq!(wave.names[3], "👋🏽  waving hand");
+        assert_eq!(wave.search, "👋  waving hand wave hello bye");
+
+        assert!(chars.iter().any(|c| c.variants[0] == "#\u{FE0F}\u{20E3}"));
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::Result;
+use once_cell::unsync::OnceCell;
+
+use super::{Entry, EvalInfo, InputValue, Mode};
+
+struct Source {
+    label: String,
+    mode: Box<dyn Mode>,
+}
+
+pub struct CombiMode {
+    sources: Vec<Source>,
+    // Entry names suffixed with the source label, filled once displayed and
+    // reset on refresh, as sources may change their entries.
+    names: Vec<OnceCell<String>>,
+}
+
+impl CombiMode {
+    pub fn new(sources: Vec<(String, Box<dyn Mode>)>) -> Self {
+        let mut mode = Self {
+            sources: sources
+                .into_iter()
+                .map(|(label, mode)| Source { label, mode })
+                .collect(),
+            names: vec![],
+        };
+        mode.reset_names();
+        mode
+    }
+
+    fn reset_names(&mut self) {
+        let len = self.entries_len();
+        self.names.clear();
+        self.names.resize_with(len, OnceCell::new);
+    }
+
+    // Maps combined index to the source index and the index within that source.
+    fn locate(&self, mut idx: usize) -> (usize, usize) {
+        for (source_idx, source) in self.sources.iter().enumerate() {
+            let len = source.mode.entries_len();
+            if idx < len {
+                return (source_idx, idx);
+            }
+            idx -= len;
+        }
+        panic!("combi entry index overflow");
+    }
+}
+
+impl Mode for CombiMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        // Custom input goes to the last source, i.e. dialog or binaries.
+        let (source_idx, index) = match info.index {
+            Some(idx) => {
+                let (source_idx, idx) = self.locate(idx);
+                (source_idx, Some(idx))
+            }
+            None => (self.sources.len() - 1, None),
+        };
+
+        self.sources[source_idx]
+            .mode
+            .eval(EvalInfo { index, ..info })
+    }
+
+    fn entries_len(&self) -> usize {
+        self.sources.iter().map(|s| s.mode.entries_len()).sum()
+    }
+
+    fn subentries_len(&self, idx: usize) -> usize {
+        let (source_idx, idx) = self.locate(idx);
+        self.sources[source_idx].mode.subentries_len(idx)
+    }
+
+    fn has_subname(&self, idx: usize) -> bool {
+        let (source_idx, idx) = self.locate(idx);
+        self.sources[source_idx].mode.has_subname(idx)
+    }
+
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
+        let (source_idx, local) = self.locate(idx);
+        let source = &self.sources[source_idx];
+        let entry = source.mode.entry(local, subidx);
+        let name = self.names[idx].get_or_init(|| format!("{} [{}]", entry.name, source.label));
+
+        Entry {
+            name: name.as_str(),
+            ..entry
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.sources.iter().flat_map(|s| s.mode.text_entries()))
+    }
+
+    fn update_input(&mut self, input: &InputValue<'_>) {
+        for source in &mut self.sources {
+            source.mode.update_input(input);
+        }
+    }
+
+    fn refresh(&mut self) {
+        for source in &mut self.sources {
+            source.mode.refresh();
+        }
+        self.reset_names();
+    }
+
+    fn is_loading(&self) -> bool {
+        self.sources.iter().any(|s| s.mode.is_loading())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode::DialogMode;
+    use crate::window::InputLines;
+
+    #[test]
+    fn test_growing_source() {
+        let input = InputLines::default();
+        let streaming = DialogMode::streaming(input.clone(), Default::default(), None);
+        let mut mode = CombiMode::new(vec![
+            (
+                "fixed".to_owned(),
+                Box::new(DialogMode::from_lines(vec!["a".to_owned()])),
+            ),
+            ("stdin".to_owned(), Box::new(streaming)),
+        ]);
+        assert_eq!(mode.entries_len(), 1);
+        assert!(mode.is_loading());
+
+        input.borrow_mut().read_all(&b"b\nc\n"[..]).unwrap();
+        mode.refresh();
+        assert_eq!(mode.entries_len(), 3);
+        assert_eq!(mode.entry(2, 0).name, "c [stdin]");
+        assert!(!mode.is_loading());
+    }
+}
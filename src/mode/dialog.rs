@@ -1,50 +1,674 @@
+use std::io::Write;
+use std::str::FromStr;
+
 use anyhow::{Context, Result};
+use once_cell::unsync::OnceCell;
+use serde::Deserialize;
+
+use super::{Entry, EvalInfo, Mode};
+use crate::desktop::IconLookup;
+use crate::icon::Icon;
+use crate::window::{InputLines, LineBuffer};
+use crate::Chord;
+pub use fields::Fields;
+
+mod fields;
+
+// Exit status once cancelled, so scripts can tell it from errors exiting with 1.
+pub const CANCEL_EXIT_CODE: i32 = 130;
+
+/// What is printed for the accepted line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The line, or its fields selected with `accept_nth`.
+    #[default]
+    Text,
+    /// Zero-based line index in the input, -1 for custom input.
+    Index,
+    /// The typed input.
+    Query,
+    /// JSON object with the index (-1 for custom input), text and query.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "index" => Ok(Self::Index),
+            "query" => Ok(Self::Query),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "unknown format `{s}`, expected one of: text, index, query, json"
+            )),
+        }
+    }
+}
+
+pub struct DialogOptions {
+    /// Whether several lines can be marked and printed at once.
+    pub multi: bool,
+    /// Whether lines are JSON objects rather than plain text.
+    pub json: bool,
+    /// Whether records are NUL-separated rather than lines, so they may span
+    /// several lines, the output is NUL-terminated as well.
+    pub null: bool,
+    pub format: OutputFormat,
+    /// Whether the input matching no line is rejected.
+    pub no_custom: bool,
+    /// Line selected on start, the one equal to it or at the zero-based input
+    /// index if it's a number.
+    pub select: Option<String>,
+    /// Whether the only line left by the filter is accepted right away.
+    pub auto_select: bool,
+    /// Separator of the line fields.
+    pub delimiter: String,
+    /// Fields displayed as the entry name.
+    pub with_nth: Option<Fields>,
+    /// Fields searched by the input, the displayed ones by default.
+    pub nth: Option<Fields>,
+    /// Fields printed once accepted, the whole line by default.
+    pub accept_nth: Option<Fields>,
+    /// Fields displayed as the entry subname.
+    pub subname_nth: Option<Fields>,
+    /// Keys accepting the selection with the exit code, besides Alt+1..Alt+9
+    /// exiting with 10..18.
+    pub accept_keys: Vec<(Chord, i32)>,
+}
+
+impl DialogOptions {
+    pub fn separator(&self) -> u8 {
+        if self.null {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+impl Default for DialogOptions {
+    fn default() -> Self {
+        Self {
+            multi: false,
+            json: false,
+            null: false,
+            format: OutputFormat::Text,
+            no_custom: false,
+            select: None,
+            auto_select: false,
+            delimiter: "\t".into(),
+            with_nth: None,
+            nth: None,
+            accept_nth: None,
+            subname_nth: None,
+            accept_keys: vec![],
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonAction {
+    Name(String),
+    Full { name: String, value: Option<String> },
+}
+
+// `{"name": "..", "subname": "..", "icon": "..", "value": "..", "actions": [..]}`
+#[derive(Deserialize)]
+struct JsonLine {
+    name: String,
+    subname: Option<String>,
+    icon: Option<String>,
+    value: Option<String>,
+    #[serde(default)]
+    actions: Vec<JsonAction>,
+}
+
+struct Action {
+    name: String,
+    value: Option<String>,
+}
+
+struct Line {
+    // position in the input, invalid JSON lines are skipped
+    input_idx: usize,
+    // the whole line or the JSON name
+    text: String,
+    // selected fields, if differ from the whole line
+    name: Option<String>,
+    search: Option<String>,
+    subname: Option<String>,
+    // theme name or path, resolved once displayed
+    icon_name: Option<String>,
+    icon: OnceCell<Option<Icon>>,
+    // printed instead of the text
+    value: Option<String>,
+    actions: Vec<Action>,
+}
+
+impl Line {
+    fn from_json(line: &str) -> serde_json::Result<Self> {
+        let JsonLine {
+            name,
+            subname,
+            icon,
+            value,
+            actions,
+        } = serde_json::from_str(line)?;
+
+        Ok(Self {
+            input_idx: 0,
+            text: name,
+            name: None,
+            search: None,
+            subname,
+            icon_name: icon,
+            icon: OnceCell::new(),
+            value,
+            actions: actions
+                .into_iter()
+                .map(|a| match a {
+                    JsonAction::Name(name) => Action { name, value: None },
+                    JsonAction::Full { name, value } => Action { name, value },
+                })
+                .collect(),
+        })
+    }
 
-use super::{Entry, EvalInfo};
+    fn new(text: String, options: &DialogOptions) -> Self {
+        let select = |fields: &Option<Fields>| {
+            fields
+                .as_ref()
+                .map(|f| f.select(&text, &options.delimiter, " ").unwrap_or_default())
+        };
+
+        // Multi-line records show the first line, the rest is previewed.
+        let (first_line, preview) = match text.split_once('\n') {
+            Some((first, rest)) => {
+                let rest: Vec<_> = rest
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .collect();
+                (
+                    Some(first.to_owned()),
+                    Some(rest.join(" ")).filter(|p| !p.is_empty()),
+                )
+            }
+            None => (None, None),
+        };
+
+        Self {
+            input_idx: 0,
+            name: select(&options.with_nth).or(first_line),
+            search: select(&options.nth),
+            subname: select(&options.subname_nth)
+                .filter(|s| !s.is_empty())
+                .or(preview),
+            text,
+            icon_name: None,
+            icon: OnceCell::new(),
+            value: None,
+            actions: vec![],
+        }
+    }
+
+    // `offset` is the input position of the first line.
+    fn parse_all(lines: Vec<String>, offset: usize, options: &DialogOptions) -> Vec<Self> {
+        lines
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, text)| {
+                let line = if !options.json {
+                    Line::new(text, options)
+                } else if text.trim().is_empty() {
+                    return None;
+                } else {
+                    Line::from_json(&text)
+                        .map_err(|e| log::warn!("invalid JSON line `{text}`: {e}"))
+                        .ok()?
+                };
+                Some(Self {
+                    input_idx: offset + i,
+                    ..line
+                })
+            })
+            .collect()
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.text)
+    }
+
+    fn search(&self) -> &str {
+        self.search.as_deref().unwrap_or_else(|| self.name())
+    }
+}
 
 pub struct DialogMode {
-    lines: Vec<String>,
+    lines: Vec<Line>,
+    options: DialogOptions,
+    icon_lookup: Option<IconLookup>,
+    // lines still arriving, if streamed
+    input: Option<InputLines>,
+    // number of input lines read so far
+    input_len: usize,
+    // `DialogOptions::select` until the line is found
+    select: Option<String>,
+    // found line to select, until taken
+    preselected: Option<usize>,
+    // marked line indices, in the order of marking
+    marked: Vec<usize>,
 }
 
 impl DialogMode {
-    pub fn new() -> Result<Self> {
-        std::io::stdin()
-            .lines()
-            .collect::<Result<_, _>>()
-            .context("failed to read stdin")
-            .map(|lines| Self { lines })
+    /// Reads lines from stdin, icons of JSON lines are resolved with the
+    /// `icon_lookup`.
+    pub fn new(options: DialogOptions, icon_lookup: Option<IconLookup>) -> Result<Self> {
+        let mut buffer = LineBuffer::new(options.separator());
+        buffer
+            .read_all(std::io::stdin().lock())
+            .context("failed to read stdin")?;
+        let lines = buffer.take_lines();
+        Ok(Self {
+            icon_lookup,
+            ..Self::with_options(lines, options)
+        })
+    }
+
+    /// Shows lines as they are read from the `input` by the window.
+    pub fn streaming(
+        input: InputLines,
+        options: DialogOptions,
+        icon_lookup: Option<IconLookup>,
+    ) -> Self {
+        Self {
+            icon_lookup,
+            input: Some(input),
+            ..Self::with_options(vec![], options)
+        }
     }
 
     pub fn from_lines(lines: Vec<String>) -> Self {
-        Self { lines }
+        Self::with_options(lines, DialogOptions::default())
+    }
+
+    pub fn with_options(lines: Vec<String>, mut options: DialogOptions) -> Self {
+        // As in rofi, `kb-custom-1` is Alt+1 and exits with 10.
+        options.accept_keys.extend((1..=9).map(|n| {
+            let chord = format!("alt+{n}").parse().expect("invalid default chord");
+            (chord, 9 + n)
+        }));
+
+        let mut mode = Self {
+            input_len: lines.len(),
+            lines: Line::parse_all(lines, 0, &options),
+            select: options.select.clone(),
+            preselected: None,
+            options,
+            icon_lookup: None,
+            input: None,
+            marked: vec![],
+        };
+        mode.find_selected(0);
+        mode
+    }
+
+    // Looks for the line to select starting with the `from` line.
+    fn find_selected(&mut self, from: usize) {
+        let Some(select) = &self.select else {
+            return;
+        };
+        let input_idx = select.parse::<usize>().ok();
+
+        let found = self.lines[from..]
+            .iter()
+            .position(|l| l.text == *select || Some(l.input_idx) == input_idx);
+        if let Some(idx) = found {
+            self.preselected = Some(from + idx);
+            self.select = None;
+        }
+    }
+
+    fn value(&self, idx: usize, subidx: usize) -> String {
+        let line = &self.lines[idx];
+        if let Some(action) = subidx.checked_sub(1).and_then(|i| line.actions.get(i)) {
+            return action.value.clone().unwrap_or_else(|| action.name.clone());
+        }
+        if let Some(value) = &line.value {
+            return value.clone();
+        }
+
+        let text = &line.text;
+        match &self.options.accept_nth {
+            Some(fields) => fields
+                .select(text, &self.options.delimiter, &self.options.delimiter)
+                .unwrap_or_default(),
+            None => text.clone(),
+        }
     }
 
-    pub fn eval(&mut self, info: EvalInfo<'_>) -> Result<std::convert::Infallible> {
-        let value = info
-            .index
-            .and_then(|idx| Some(self.lines.get(idx)?.as_str()))
-            .unwrap_or(info.input_value.source);
-        println!("{value}");
-        std::process::exit(0);
+    fn format(&self, idx: Option<usize>, text: String, query: &str) -> String {
+        // -1 for custom input
+        let input_idx = idx.map_or(-1, |idx| self.lines[idx].input_idx as i64);
+        match self.options.format {
+            OutputFormat::Text => text,
+            OutputFormat::Index => input_idx.to_string(),
+            OutputFormat::Query => query.to_owned(),
+            OutputFormat::Json => serde_json::json!({
+                "index": input_idx,
+                "text": text,
+                "query": query,
+            })
+            .to_string(),
+        }
     }
 
-    pub fn entries_len(&self) -> usize {
+    // Printed lines, formatted.
+    fn selected(&self, info: &EvalInfo<'_>) -> Vec<String> {
+        let query = info.input_value.source;
+        if self.options.format == OutputFormat::Query {
+            return vec![query.to_owned()];
+        }
+        if !self.marked.is_empty() {
+            return self
+                .marked
+                .iter()
+                .map(|&idx| self.format(Some(idx), self.value(idx, 0), query))
+                .collect();
+        }
+
+        let idx = info.index.filter(|&idx| idx < self.lines.len());
+        let text = match idx {
+            Some(idx) => self.value(idx, info.subindex),
+            None => query.to_owned(),
+        };
+        vec![self.format(idx, text, query)]
+    }
+}
+
+impl Mode for DialogMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        // Flushed explicitly, as NUL doesn't flush the line buffer.
+        let terminator = char::from(self.options.separator());
+        let mut stdout = std::io::stdout().lock();
+        for value in self.selected(&info) {
+            write!(stdout, "{value}{terminator}")?;
+        }
+        stdout.flush()?;
+        std::process::exit(info.exit_code.unwrap_or(0));
+    }
+
+    fn entries_len(&self) -> usize {
         self.lines.len()
     }
 
-    pub fn subentries_len(&self, _: usize) -> usize {
-        0
+    fn subentries_len(&self, idx: usize) -> usize {
+        self.lines[idx].actions.len()
+    }
+
+    fn has_subname(&self, idx: usize) -> bool {
+        let line = &self.lines[idx];
+        line.subname.is_some() || !line.actions.is_empty()
     }
 
-    pub fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
+        let line = &self.lines[idx];
+        let subname = match subidx.checked_sub(1) {
+            Some(action) => Some(line.actions[action].name.as_str()),
+            None => line.subname.as_deref(),
+        };
+        let icon = line
+            .icon
+            .get_or_init(|| {
+                let name = line.icon_name.as_deref()?;
+                self.icon_lookup.as_ref()?.find_icon(name)
+            })
+            .as_ref();
+
         Entry {
-            name: self.lines[idx].as_ref(),
-            subname: None,
-            icon: None,
+            name: line.name(),
+            subname,
+            icon: icon.and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.lines.iter().map(Line::search))
+    }
+
+    fn highlights_matches(&self) -> bool {
+        self.options.nth.is_none()
+    }
+
+    fn toggle_mark(&mut self, idx: usize) -> bool {
+        if !self.options.multi {
+            return false;
+        }
+
+        match self.marked.iter().position(|&m| m == idx) {
+            Some(pos) => {
+                self.marked.remove(pos);
+            }
+            None => self.marked.push(idx),
+        }
+        true
+    }
+
+    fn is_marked(&self, idx: usize) -> bool {
+        self.marked.contains(&idx)
+    }
+
+    fn refresh(&mut self) {
+        let Some(input) = &self.input else {
+            return;
+        };
+        let lines = input.borrow_mut().take_lines();
+        if !lines.is_empty() {
+            let offset = self.input_len;
+            self.input_len += lines.len();
+
+            let from = self.lines.len();
+            self.lines
+                .extend(Line::parse_all(lines, offset, &self.options));
+            self.find_selected(from);
         }
     }
 
-    pub fn text_entries(&self) -> impl super::ExactSizeIterator<Item = &str> {
-        self.lines.iter().map(|e| e.as_str())
+    fn take_preselected(&mut self) -> Option<usize> {
+        self.preselected.take()
+    }
+
+    fn accepts_custom_input(&self) -> bool {
+        !self.options.no_custom || !self.marked.is_empty()
+    }
+
+    fn auto_accepts(&self) -> bool {
+        self.options.auto_select
+    }
+
+    fn accept_keys(&self) -> &[(Chord, i32)] {
+        &self.options.accept_keys
+    }
+
+    fn cancel_exit_code(&self) -> Option<i32> {
+        Some(CANCEL_EXIT_CODE)
+    }
+
+    fn is_loading(&self) -> bool {
+        self.input.as_ref().is_some_and(|i| i.borrow().is_loading())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::input_parser::InputValue;
+
+    fn selected(mode: &DialogMode, index: usize) -> Vec<String> {
+        let input = InputValue::empty();
+        mode.selected(&EvalInfo {
+            index: Some(index),
+            subindex: 0,
+            input_value: &input,
+            exit_code: None,
+        })
+    }
+
+    #[test]
+    fn test_marked_order() {
+        let lines = ["a", "b", "c"].map(String::from).to_vec();
+        let options = DialogOptions {
+            multi: true,
+            ..Default::default()
+        };
+        let mut mode = DialogMode::with_options(lines, options);
+        assert!(mode.toggle_mark(2));
+        assert!(mode.toggle_mark(0));
+        assert!(mode.toggle_mark(1));
+        assert!(mode.toggle_mark(0));
+
+        assert_eq!(selected(&mode, 0), ["c", "b"]);
+    }
+
+    #[test_case(OutputFormat::Text, "b"; "text")]
+    #[test_case(OutputFormat::Index, "2"; "index")]
+    #[test_case(OutputFormat::Query, ""; "query")]
+    #[test_case(OutputFormat::Json, r#"{"index":2,"query":"","text":"b"}"#; "json")]
+    fn test_format(format: OutputFormat, expected: &str) {
+        // The invalid line is skipped, but still counted in the input.
+        let lines = [r#"{"name": "a"}"#, "invalid", r#"{"name": "b"}"#];
+        let options = DialogOptions {
+            json: true,
+            format,
+            ..Default::default()
+        };
+        let mode = DialogMode::with_options(lines.map(String::from).to_vec(), options);
+
+        assert_eq!(selected(&mode, 1), [expected]);
+    }
+
+    #[test_case(OutputFormat::Index, "-1"; "index")]
+    #[test_case(OutputFormat::Json, r#"{"index":-1,"query":"","text":""}"#; "json")]
+    fn test_custom_input_index(format: OutputFormat, expected: &str) {
+        let options = DialogOptions {
+            format,
+            ..Default::default()
+        };
+        let mode = DialogMode::with_options(vec!["a".to_owned()], options);
+        let input = InputValue::empty();
+        let info = EvalInfo {
+            index: None,
+            subindex: 0,
+            input_value: &input,
+            exit_code: None,
+        };
+
+        assert_eq!(mode.selected(&info), [expected]);
+    }
+
+    #[test_case("b", Some(1); "by text")]
+    #[test_case("2", Some(2); "by index")]
+    #[test_case("d", None; "missing")]
+    fn test_select(select: &str, expected: Option<usize>) {
+        let options = DialogOptions {
+            select: Some(select.to_owned()),
+            ..Default::default()
+        };
+        let mut mode =
+            DialogMode::with_options(["a", "b", "c"].map(String::from).to_vec(), options);
+
+        assert_eq!(mode.take_preselected(), expected);
+        assert_eq!(mode.take_preselected(), None);
+    }
+
+    #[test]
+    fn test_multi_line() {
+        let lines = vec!["first\n  second\n\nthird".to_owned(), "single".to_owned()];
+        let options = DialogOptions {
+            null: true,
+            ..Default::default()
+        };
+        let mode = DialogMode::with_options(lines, options);
+
+        let entry = mode.entry(0, 0);
+        assert_eq!(entry.name, "first");
+        assert_eq!(entry.subname, Some("second third"));
+        assert_eq!(mode.entry(1, 0).subname, None);
+        assert_eq!(selected(&mode, 0), ["first\n  second\n\nthird"]);
+    }
+
+    #[test]
+    fn test_accept_keys() {
+        use sctk::seat::keyboard::{Keysym, Modifiers};
+
+        let options = DialogOptions {
+            accept_keys: vec![("alt+1".parse().unwrap(), 42)],
+            ..Default::default()
+        };
+        let mode = DialogMode::with_options(vec![], options);
+        let alt = Modifiers {
+            alt: true,
+            ..Default::default()
+        };
+        let exit_code = |keysym| {
+            mode.accept_keys()
+                .iter()
+                .find(|(chord, _)| chord.matches(keysym, alt))
+                .map(|&(_, code)| code)
+        };
+
+        assert_eq!(exit_code(Keysym::_1), Some(42));
+        assert_eq!(exit_code(Keysym::_9), Some(18));
+        assert_eq!(exit_code(Keysym::_0), None);
+    }
+
+    #[test]
+    fn test_json() {
+        let lines = [
+            r#"{"name": "Open", "subname": "in editor", "icon": "text-editor", "value": "open", "actions": ["Copy", {"name": "Delete", "value": "rm"}]}"#,
+            r#"{"name": "Plain"}"#,
+            "not a json",
+        ];
+        let options = DialogOptions {
+            json: true,
+            ..Default::default()
+        };
+        let mode = DialogMode::with_options(lines.map(String::from).to_vec(), options);
+
+        assert_eq!(mode.entries_len(), 2);
+        assert_eq!(mode.subentries_len(0), 2);
+        assert_eq!(mode.entry(0, 0).subname, Some("in editor"));
+        assert_eq!(mode.entry(0, 2).subname, Some("Delete"));
+        assert_eq!(mode.value(0, 0), "open");
+        assert_eq!(mode.value(0, 1), "Copy");
+        assert_eq!(mode.value(0, 2), "rm");
+        assert_eq!(mode.value(1, 0), "Plain");
+    }
+
+    #[test]
+    fn test_fields() {
+        let lines = vec!["42\tFirefox\tWeb browser".to_owned()];
+        let options = DialogOptions {
+            with_nth: Some("2".parse().unwrap()),
+            nth: Some("2..".parse().unwrap()),
+            accept_nth: Some("1".parse().unwrap()),
+            subname_nth: Some("3".parse().unwrap()),
+            ..Default::default()
+        };
+        let mode = DialogMode::with_options(lines, options);
+
+        let entry = mode.entry(0, 0);
+        assert_eq!(entry.name, "Firefox");
+        assert_eq!(entry.subname, Some("Web browser"));
+        assert_eq!(
+            mode.text_entries().collect::<Vec<_>>(),
+            ["Firefox Web browser"]
+        );
+        assert_eq!(selected(&mode, 0), ["42"]);
     }
 }
@@ -0,0 +1,114 @@
+use std::str::FromStr;
+
+// 1-based, negative ones are counted from the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Index {
+    Start(usize),
+    End(usize),
+}
+
+impl Index {
+    fn resolve(self, len: usize) -> Option<usize> {
+        match self {
+            Index::Start(n) => Some(n - 1),
+            Index::End(n) => len.checked_sub(n),
+        }
+    }
+}
+
+impl FromStr for Index {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from_end, n) = match s.strip_prefix('-') {
+            Some(n) => (true, n),
+            None => (false, s),
+        };
+        match n.parse::<usize>() {
+            Ok(0) | Err(_) => Err(format!("invalid field index `{s}`")),
+            Ok(n) if from_end => Ok(Index::End(n)),
+            Ok(n) => Ok(Index::Start(n)),
+        }
+    }
+}
+
+/// Field selection like `1`, `-1`, `2..`, `..3` or `1..-2`, separated by
+/// commas, as in `fzf --nth`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fields(Vec<(Option<Index>, Option<Index>)>);
+
+impl FromStr for Fields {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_bound = |b: &str| (!b.is_empty()).then(|| b.parse()).transpose();
+
+        s.split(',')
+            .map(|range| match range.split_once("..") {
+                Some((start, end)) => Ok((parse_bound(start)?, parse_bound(end)?)),
+                None => {
+                    let idx = range.parse()?;
+                    Ok((Some(idx), Some(idx)))
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Fields)
+    }
+}
+
+impl Fields {
+    /// Splits the line by the delimiter and joins the selected fields with
+    /// the `separator`, returns `None` if nothing is selected.
+    pub fn select(&self, line: &str, delimiter: &str, separator: &str) -> Option<String> {
+        let fields: Vec<_> = line.split(delimiter).collect();
+
+        let mut selected: Vec<&str> = vec![];
+        for &(start, end) in &self.0 {
+            let start = start.map_or(Some(0), |s| s.resolve(fields.len()));
+            let end = end.map_or(Some(fields.len() - 1), |e| e.resolve(fields.len()));
+            let (Some(start), Some(end)) = (start, end) else {
+                continue;
+            };
+            selected.extend(fields.iter().take(end + 1).skip(start));
+        }
+
+        (!selected.is_empty()).then(|| selected.join(separator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("2", "b"; "single")]
+    #[test_case("-1", "d"; "from end")]
+    #[test_case("3,1", "c a"; "list")]
+    #[test_case("2..3", "b c"; "range")]
+    #[test_case("3..", "c d"; "open end")]
+    #[test_case("..2", "a b"; "open start")]
+    #[test_case("2..-2", "b c"; "range to end")]
+    fn test_select(fields: &str, expected: &str) {
+        let fields: Fields = fields.parse().unwrap();
+        assert_eq!(
+            fields.select("a\tb\tc\td", "\t", " ").as_deref(),
+            Some(expected)
+        );
+    }
+
+    #[test_case("5"; "out of range")]
+    #[test_case("-5"; "out of range from end")]
+    fn test_select_none(fields: &str) {
+        let fields: Fields = fields.parse().unwrap();
+        assert_eq!(fields.select("a\tb", "\t", " "), None);
+    }
+
+    #[test_case("0")]
+    #[test_case("x")]
+    #[test_case("1..y")]
+    #[test_case("")]
+    fn test_invalid(fields: &str) {
+        assert!(fields.parse::<Fields>().is_err());
+    }
+}
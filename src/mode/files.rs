@@ -0,0 +1,186 @@
+use std::ffi::CString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use once_cell::unsync::OnceCell;
+
+use super::{Entry, EvalInfo, Mode};
+use crate::desktop::IconLookup;
+use crate::icon::Icon;
+use crate::mime::MimeDb;
+
+struct File {
+    // file name as displayed, with trailing slash for directories
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+    icon: OnceCell<Option<Icon>>,
+}
+
+pub struct FilesMode {
+    dir: PathBuf,
+    files: Vec<File>,
+    show_hidden: bool,
+    print: bool,
+    icons: Option<(IconLookup, MimeDb)>,
+}
+
+impl FilesMode {
+    pub fn new(
+        dir: PathBuf,
+        show_hidden: bool,
+        print: bool,
+        icon_lookup: Option<IconLookup>,
+    ) -> Result<Self> {
+        let mut mode = Self {
+            dir: PathBuf::new(),
+            files: vec![],
+            show_hidden,
+            print,
+            icons: icon_lookup.map(|lookup| (lookup, MimeDb::load())),
+        };
+        mode.change_dir(&dir)?;
+        Ok(mode)
+    }
+
+    fn change_dir(&mut self, dir: &Path) -> Result<()> {
+        let dir = dir
+            .canonicalize()
+            .with_context(|| format!("cannot open {}", dir.display()))?;
+
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .with_context(|| format!("cannot read {}", dir.display()))?
+            .flatten()
+            .filter_map(|entry| {
+                // Non-UTF-8 names are displayed lossily, but opened by the path.
+                let mut name = entry.file_name().to_string_lossy().into_owned();
+                if !self.show_hidden && name.starts_with('.') {
+                    return None;
+                }
+
+                // Follow symlinks, so linked directories are entered too.
+                let path = entry.path();
+                let is_dir = path.is_dir();
+                if is_dir {
+                    name.push('/');
+                }
+
+                Some(File {
+                    name,
+                    path,
+                    is_dir,
+                    icon: OnceCell::new(),
+                })
+            })
+            .collect();
+
+        files.sort_by(|x, y| {
+            y.is_dir
+                .cmp(&x.is_dir)
+                .then_with(|| x.name.to_lowercase().cmp(&y.name.to_lowercase()))
+        });
+
+        self.dir = dir;
+        self.files = files;
+        Ok(())
+    }
+
+    fn find_icon(&self, file: &File) -> Option<Icon> {
+        let (lookup, mime_db) = self.icons.as_ref()?;
+        let mime = mime_db.guess(&file.path);
+        // For unknown types or ones without icons in the theme.
+        let generic = match mime {
+            _ if file.is_dir => None,
+            Some(mime) if mime.starts_with("text/") => Some("text-x-generic"),
+            _ => Some("application-x-generic"),
+        };
+
+        file.is_dir
+            .then(|| "folder".to_owned())
+            .into_iter()
+            .chain(mime.map(|m| mime_db.icon_names(m)).into_iter().flatten())
+            .chain(generic.map(str::to_owned))
+            .find_map(|name| lookup.find_icon(&name))
+    }
+
+    fn open(&mut self, path: &Path, info: &EvalInfo<'_>) -> Result<()> {
+        if path.is_dir() {
+            return self.change_dir(path);
+        }
+
+        if self.print {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(path.as_os_str().as_encoded_bytes())?;
+            stdout.write_all(b"\n")?;
+            stdout.flush()?;
+            std::process::exit(0);
+        }
+
+        let path = CString::new(path.as_os_str().as_encoded_bytes()).context("invalid path")?;
+        crate::exec::exec(
+            None,
+            [CString::new("xdg-open").unwrap(), path],
+            info.input_value,
+        )
+        .map(|x| match x {})
+    }
+}
+
+impl Mode for FilesMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        // `~dir` jumps to the directory, `~` alone to the home one.
+        if let Some(dir) = info.working_dir {
+            let dir = if dir.is_empty() {
+                std::env::var_os("HOME")
+                    .map(PathBuf::from)
+                    .unwrap_or_default()
+            } else {
+                self.dir.join(dir)
+            };
+            return self.change_dir(&dir);
+        }
+
+        let path = match info.index {
+            Some(idx) => self.files[idx].path.clone(),
+            None => self.dir.join(info.search_string),
+        };
+        self.open(&path, &info)
+    }
+
+    fn entries_len(&self) -> usize {
+        self.files.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let file = &self.files[idx];
+
+        Entry {
+            name: file.name.as_str(),
+            subname: None,
+            icon: file
+                .icon
+                .get_or_init(|| self.find_icon(file))
+                .as_ref()
+                .and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.files.iter().map(|f| f.name.as_str()))
+    }
+
+    fn back(&mut self) -> bool {
+        let Some(parent) = self.dir.parent().map(Path::to_owned) else {
+            return false;
+        };
+
+        self.change_dir(&parent)
+            .map_err(|e| log::warn!("{e:#}"))
+            .is_ok()
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use once_cell::unsync::OnceCell;
+
+use super::{Entry, EvalInfo, Mode};
+
+const DEFAULT_MANPATH: &[&str] = &["/usr/local/share/man", "/usr/share/man"];
+
+// NAME section is at the top, no need to decompress whole pages.
+const MAX_LINES: usize = 200;
+
+// Pages are `<name>.<section>[.gz]`, e.g. `systemd.unit.5.gz`.
+fn parse_file_name(file_name: &str) -> Option<(&str, &str)> {
+    let file_name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    let (name, section) = file_name.rsplit_once('.')?;
+    (!name.is_empty() && section.starts_with(|c: char| c.is_ascii_digit() || c == 'n'))
+        .then_some((name, section))
+}
+
+// Drops font changes and common escapes of the roff source.
+fn strip_roff(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            stripped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('f') => {
+                chars.next();
+            }
+            Some('(') => {
+                let code: String = chars.by_ref().take(2).collect();
+                stripped.push_str(match code.as_str() {
+                    "em" | "en" => "-",
+                    "lq" | "rq" => "\"",
+                    _ => "",
+                });
+            }
+            Some('&') | None => {}
+            Some(c) => stripped.push(c),
+        }
+    }
+    stripped.trim().trim_matches('"').to_owned()
+}
+
+/// Finds the page description in its NAME section, both `man` and `mdoc`
+/// macros are supported.
+fn parse_description(lines: impl Iterator<Item = String>) -> Option<String> {
+    let mut in_name = false;
+    let mut text = String::new();
+
+    for line in lines.take(MAX_LINES) {
+        let line = line.trim();
+        let (is_section, title) = match line.split_once(' ') {
+            Some((".SH" | ".Sh", title)) => (true, title.trim().trim_matches('"')),
+            _ => (false, ""),
+        };
+
+        if is_section {
+            if in_name {
+                break;
+            }
+            in_name = title.eq_ignore_ascii_case("NAME");
+        } else if let Some(description) = line.strip_prefix(".Nd ") {
+            return Some(strip_roff(description));
+        } else if in_name && !line.starts_with('.') && !line.starts_with('\'') {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(line);
+        }
+    }
+
+    // `name, alias \- description`
+    let (_, description) = text.split_once("\\-").or_else(|| text.split_once(" - "))?;
+    Some(strip_roff(description))
+}
+
+fn read_description(path: &Path) -> Option<String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| log::debug!("cannot open {}: {e}", path.display()))
+        .ok()?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|e| e == "gz") {
+        Box::new(flate2::read::GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    parse_description(BufReader::new(reader).lines().map_while(Result::ok))
+}
+
+fn manpath() -> Vec<PathBuf> {
+    let Some(manpath) = std::env::var_os("MANPATH") else {
+        return DEFAULT_MANPATH.iter().map(PathBuf::from).collect();
+    };
+
+    // Empty component stands for the default path.
+    let mut dirs: Vec<PathBuf> = vec![];
+    for dir in std::env::split_paths(&manpath) {
+        if dir.as_os_str().is_empty() {
+            dirs.extend(DEFAULT_MANPATH.iter().map(PathBuf::from));
+        } else {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+struct Page {
+    name: String,
+    section: String,
+    // `name(section)`, as displayed
+    title: String,
+    path: PathBuf,
+    description: OnceCell<Option<String>>,
+}
+
+pub struct ManMode {
+    pages: Vec<Page>,
+    term: Vec<CString>,
+}
+
+impl ManMode {
+    pub fn new(term: Vec<CString>) -> Self {
+        let mut seen = HashSet::new();
+        let mut pages = vec![];
+
+        let section_dirs = manpath()
+            .into_iter()
+            .flat_map(|dir| std::fs::read_dir(dir).into_iter().flatten().flatten())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("man"));
+        for section_dir in section_dirs {
+            for file in std::fs::read_dir(section_dir.path())
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let file_name = file.file_name();
+                let Some((name, section)) = file_name.to_str().and_then(parse_file_name) else {
+                    continue;
+                };
+                // Earlier directories take precedence.
+                if !seen.insert((name.to_owned(), section.to_owned())) {
+                    continue;
+                }
+
+                pages.push(Page {
+                    title: format!("{name}({section})"),
+                    name: name.to_owned(),
+                    section: section.to_owned(),
+                    path: file.path(),
+                    description: OnceCell::new(),
+                });
+            }
+        }
+        pages.sort_by(|x, y| x.name.cmp(&y.name).then_with(|| x.section.cmp(&y.section)));
+
+        Self { pages, term }
+    }
+}
+
+impl Mode for ManMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let args = match info.index {
+            Some(idx) => vec!["man", &self.pages[idx].section, &self.pages[idx].name],
+            None => vec!["man", info.input_value.source],
+        };
+        let args = args
+            .into_iter()
+            .map(CString::new)
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid man page name")?;
+
+        crate::exec::exec(Some(std::mem::take(&mut self.term)), args, info.input_value)
+            .map(|x| match x {})
+    }
+
+    fn entries_len(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, _: usize) -> bool {
+        true
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let page = &self.pages[idx];
+
+        Entry {
+            name: page.title.as_str(),
+            subname: page
+                .description
+                .get_or_init(|| read_description(&page.path))
+                .as_deref(),
+            icon: None,
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.pages.iter().map(|p| p.title.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("ls.1.gz", Some(("ls", "1")); "compressed")]
+    #[test_case("printf.3p", Some(("printf", "3p")); "subsection")]
+    #[test_case("systemd.unit.5.gz", Some(("systemd.unit", "5")); "dotted name")]
+    #[test_case("README", None; "not a page")]
+    #[test_case("index.db", None; "non-numeric section")]
+    fn test_parse_file_name(file_name: &str, expected: Option<(&str, &str)>) {
+        assert_eq!(parse_file_name(file_name), expected);
+    }
+
+    #[test_case(
+        ".TH LS \"1\"\n.SH NAME\nls \\- list directory contents\n.SH SYNOPSIS\n.B ls",
+        Some("list directory contents");
+        "man"
+    )]
+    #[test_case(
+        ".TH GIT-LOG 1\n.SH \"NAME\"\ngit-log \\- Show commit logs\n.SH \"SYNOPSIS\"",
+        Some("Show commit logs");
+        "quoted section"
+    )]
+    #[test_case(
+        ".Dd $Mdocdate$\n.Dt SSH 1\n.Sh NAME\n.Nm ssh\n.Nd OpenSSH remote login client\n.Sh SYNOPSIS",
+        Some("OpenSSH remote login client");
+        "mdoc"
+    )]
+    #[test_case(
+        ".SH NAME\n\\fBcurl\\fR \\- transfer a URL\n",
+        Some("transfer a URL");
+        "font escapes"
+    )]
+    #[test_case(".so man1/bzip2.1", None; "redirect")]
+    fn test_parse_description(page: &str, expected: Option<&str>) {
+        assert_eq!(
+            parse_description(page.lines().map(str::to_owned)).as_deref(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::cmp::Reverse;
+use std::ffi::CString;
+
+use anyhow::{Context, Result};
+
+use super::{Entry, EvalInfo, Mode};
+use crate::config::MenuEntry;
+use crate::desktop::IconLookup;
+use crate::icon::Icon;
+use crate::usage_cache::Usage;
+
+struct Item {
+    entry: MenuEntry,
+    icon: Option<Icon>,
+}
+
+pub struct MenuMode {
+    items: Vec<Item>,
+    cache_path: String,
+    term: Vec<CString>,
+    usage: Usage,
+}
+
+impl MenuMode {
+    pub fn new(
+        name: &str,
+        entries: Vec<MenuEntry>,
+        term: Vec<CString>,
+        icon_lookup: Option<IconLookup>,
+    ) -> Self {
+        let cache_path = format!("{}.menu-{name}.cache", crate::prog_name!());
+        let usage = Usage::from_path(&cache_path);
+
+        let mut items: Vec<_> = entries
+            .into_iter()
+            .map(|entry| Item {
+                icon: entry
+                    .icon
+                    .as_deref()
+                    .zip(icon_lookup.as_ref())
+                    .and_then(|(name, lookup)| lookup.find_icon(name)),
+                entry,
+            })
+            .collect();
+        items.sort_by_key(|i| Reverse(usage.entry_count(&i.entry.name)));
+
+        Self {
+            items,
+            cache_path,
+            term,
+            usage,
+        }
+    }
+}
+
+impl Mode for MenuMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let idx = info.index.context("no menu entry remain to run")?;
+        let entry = &self.items[idx].entry;
+
+        let args = shlex::split(&entry.exec)
+            .with_context(|| format!("invalid menu command line: {}", entry.exec))?
+            .into_iter()
+            .map(|s| CString::new(s).expect("invalid argument"));
+
+        self.usage.increment_entry_usage(entry.name.clone());
+        self.usage.try_update_cache(&self.cache_path);
+
+        let term = if entry.terminal {
+            Some(std::mem::take(&mut self.term))
+        } else {
+            None
+        };
+
+        crate::exec::exec(term, args, info.input_value).map(|x| match x {})
+    }
+
+    fn entries_len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, idx: usize) -> bool {
+        self.items[idx].entry.subname.is_some()
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let item = &self.items[idx];
+
+        Entry {
+            name: item.entry.name.as_str(),
+            subname: item.entry.subname.as_deref(),
+            icon: item.icon.as_ref().and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.items.iter().map(|i| i.entry.name.as_str()))
+    }
+}
@@ -0,0 +1,98 @@
+use std::ffi::CString;
+
+use anyhow::{Context, Result};
+
+use super::{Entry, EvalInfo, Mode};
+use crate::desktop::mimeapps::MimeApps;
+use crate::DesktopEntry;
+
+pub struct OpenWithMode {
+    file: String,
+    entries: Vec<DesktopEntry>,
+    has_default: bool,
+    term: Vec<CString>,
+}
+
+impl OpenWithMode {
+    /// Lists applications for the file of `mime_types` (the type itself
+    /// followed by its parents), the default one comes first.
+    pub fn new(
+        file: String,
+        mime_types: &[&str],
+        mut entries: Vec<DesktopEntry>,
+        mime_apps: &MimeApps,
+        term: Vec<CString>,
+    ) -> Self {
+        let default = mime_apps.default_app(mime_types, |app| {
+            entries.iter().any(|e| e.desktop_fname == app)
+        });
+
+        let mut apps = vec![];
+        for app in default.into_iter().chain(mime_apps.associated(mime_types)) {
+            if let Some(pos) = entries.iter().position(|e| e.desktop_fname == app) {
+                apps.push(entries.swap_remove(pos));
+            }
+        }
+
+        // Entries may declare types they handle without being in the cache yet.
+        entries.sort_by(|x, y| x.name.cmp(&y.name));
+        apps.extend(entries.into_iter().filter(|e| {
+            mime_types.iter().any(|&mime| {
+                e.mime_types.iter().any(|m| m == mime)
+                    && !mime_apps.is_removed(mime, &e.desktop_fname)
+            })
+        }));
+
+        Self {
+            file,
+            entries: apps,
+            has_default: default.is_some(),
+            term,
+        }
+    }
+}
+
+impl Mode for OpenWithMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let idx = info.index.context("no app remain to open the file")?;
+        let entry = &self.entries[idx];
+
+        let args = crate::desktop::expand_exec(&entry.entry.exec, &[&self.file])?
+            .into_iter()
+            .map(|s| CString::new(s).expect("invalid argument"));
+
+        let term = if entry.is_terminal {
+            Some(std::mem::take(&mut self.term))
+        } else {
+            None
+        };
+
+        crate::exec::exec(term, args, info.input_value).map(|x| match x {})
+    }
+
+    fn entries_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, idx: usize) -> bool {
+        idx == 0 && self.has_default
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let entry = &self.entries[idx];
+
+        Entry {
+            name: entry.entry.name.as_ref(),
+            subname: self.has_subname(idx).then_some("Default Application"),
+            icon: entry.entry.icon.as_ref().and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.entries.iter().map(|e| e.name.as_str()))
+    }
+}
@@ -0,0 +1,161 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::{Entry, EvalInfo, Mode};
+use crate::usage_cache::Usage;
+
+const CACHE_PATH: &str = concat!(crate::prog_name!(), ".passcache");
+
+// Entries are relative paths without `.gpg`, e.g. `web/example.com`.
+// Symlinked directories are followed, `visited` ones (device and inode) are
+// skipped, so links to a parent don't loop.
+fn collect_entries(
+    root: &Path,
+    dir: &Path,
+    visited: &mut HashSet<(u64, u64)>,
+    entries: &mut Vec<String>,
+) {
+    match std::fs::metadata(dir) {
+        Ok(meta) if !visited.insert((meta.dev(), meta.ino())) => return,
+        Ok(_) => {}
+        Err(e) => {
+            log::warn!("cannot read {}: {e}", dir.display());
+            return;
+        }
+    }
+
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) => {
+            log::warn!("cannot read {}: {e}", dir.display());
+            return;
+        }
+    };
+
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        // Skip `.git`, `.gpg-id` and alike.
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_entries(root, &path, visited, entries);
+        } else if path.extension().is_some_and(|e| e == "gpg") {
+            let Some(name) = path
+                .strip_prefix(root)
+                .ok()
+                .and_then(|p| p.with_extension("").to_str().map(str::to_owned))
+            else {
+                continue;
+            };
+            entries.push(name);
+        }
+    }
+}
+
+pub struct PassMode {
+    entries: Vec<String>,
+    command: Option<Vec<String>>,
+    usage: Usage,
+}
+
+impl PassMode {
+    /// Lists the password store entries, which are printed once selected or
+    /// passed as the last argument of the `command`.
+    pub fn new(command: Option<Vec<String>>) -> Self {
+        let usage = Usage::from_path(CACHE_PATH);
+
+        let root = std::env::var_os("PASSWORD_STORE_DIR")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| Path::new(&home).join(".password-store"))
+            })
+            .unwrap_or_default();
+
+        let mut entries = vec![];
+        collect_entries(&root, &root, &mut HashSet::new(), &mut entries);
+        entries.sort_by(|x, y| {
+            Reverse(usage.entry_count(x))
+                .cmp(&Reverse(usage.entry_count(y)))
+                .then_with(|| x.cmp(y))
+        });
+
+        Self {
+            entries,
+            command,
+            usage,
+        }
+    }
+}
+
+impl Mode for PassMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let entry = info
+            .index
+            .map(|idx| self.entries[idx].as_str())
+            .unwrap_or(info.input_value.source);
+
+        self.usage.increment_entry_usage(entry.to_owned());
+        self.usage.try_update_cache(CACHE_PATH);
+
+        let Some(command) = &self.command else {
+            println!("{entry}");
+            std::process::exit(0);
+        };
+
+        let args = command
+            .iter()
+            .map(String::as_str)
+            .chain([entry])
+            .map(CString::new)
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid pass command")?;
+
+        crate::exec::exec(None, args, info.input_value).map(|x| match x {})
+    }
+
+    fn entries_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        Entry {
+            name: self.entries[idx].as_str(),
+            subname: None,
+            icon: None,
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.entries.iter().map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symlink_loop() {
+        let root = std::env::temp_dir().join(format!("yofi-pass-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("web")).unwrap();
+        std::fs::write(root.join("web/example.com.gpg"), "").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("web/loop")).unwrap();
+
+        let mut entries = vec![];
+        collect_entries(&root, &root, &mut HashSet::new(), &mut entries);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(entries, ["web/example.com"]);
+    }
+}
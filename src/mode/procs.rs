@@ -0,0 +1,202 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use nix::sys::signal::Signal;
+use nix::unistd::Pid;
+
+use super::{Entry, EvalInfo, Mode};
+
+// Selected as subitems, the first one is the default.
+const SIGNALS: [Signal; 4] = [
+    Signal::SIGTERM,
+    Signal::SIGKILL,
+    Signal::SIGSTOP,
+    Signal::SIGCONT,
+];
+
+#[derive(Debug, PartialEq, Eq)]
+struct Status {
+    name: String,
+    uid: u32,
+    // in KiB, missing for kernel threads
+    rss: Option<u64>,
+}
+
+// Fields of `/proc/<pid>/status` are `Key:\tvalue` lines.
+fn parse_status(content: &str) -> Option<Status> {
+    let mut name = None;
+    let mut uid = None;
+    let mut rss = None;
+
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "Name" => name = Some(value.to_owned()),
+            // Real, effective, saved and filesystem ones.
+            "Uid" => uid = value.split_whitespace().next()?.parse().ok(),
+            "VmRSS" => rss = value.trim_end_matches("kB").trim().parse().ok(),
+            _ => {}
+        }
+    }
+
+    Some(Status {
+        name: name?,
+        uid: uid?,
+        rss,
+    })
+}
+
+fn format_size(kib: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = kib as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{kib} {}", UNITS[0])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+struct Process {
+    pid: Pid,
+    rss: u64,
+    // `name pid`, as displayed
+    name: String,
+    // subname per signal with RSS and command line
+    subnames: Vec<String>,
+    // displayed name and command line
+    search: String,
+}
+
+fn read_process(dir: &Path, pid: Pid) -> Option<Process> {
+    let status = parse_status(&std::fs::read_to_string(dir.join("status")).ok()?)?;
+    if status.uid != nix::unistd::getuid().as_raw() {
+        return None;
+    }
+
+    // Arguments are NUL-terminated.
+    let cmdline = std::fs::read(dir.join("cmdline")).ok()?;
+    let cmdline = String::from_utf8_lossy(&cmdline)
+        .split_terminator('\0')
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let rss = status.rss.unwrap_or_default();
+    let name = format!("{} {pid}", status.name);
+    let subnames = SIGNALS
+        .iter()
+        .map(|s| format!("{s} · {} · {cmdline}", format_size(rss)))
+        .collect();
+
+    Some(Process {
+        pid,
+        rss,
+        search: format!("{name} {cmdline}"),
+        name,
+        subnames,
+    })
+}
+
+pub struct ProcsMode {
+    procs: Vec<Process>,
+}
+
+impl ProcsMode {
+    /// Lists processes of the current user, the largest first.
+    pub fn new() -> Result<Self> {
+        let own_pid = nix::unistd::getpid();
+
+        let mut procs: Vec<_> = std::fs::read_dir("/proc")
+            .context("cannot read /proc")?
+            .flatten()
+            .filter_map(|e| {
+                let pid = Pid::from_raw(e.file_name().to_str()?.parse().ok()?);
+                if pid == own_pid {
+                    return None;
+                }
+                // Processes may exit while being read.
+                read_process(&e.path(), pid)
+            })
+            .collect();
+        procs.sort_by_key(|p| std::cmp::Reverse(p.rss));
+
+        Ok(Self { procs })
+    }
+}
+
+impl Mode for ProcsMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let idx = info.index.context("no process remain to signal")?;
+        let process = &self.procs[idx];
+        let signal = SIGNALS[info.subindex];
+
+        nix::sys::signal::kill(process.pid, signal)
+            .with_context(|| format!("cannot send {signal} to {}", process.pid))?;
+        std::process::exit(0);
+    }
+
+    fn entries_len(&self) -> usize {
+        self.procs.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        SIGNALS.len() - 1
+    }
+
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
+        let process = &self.procs[idx];
+
+        Entry {
+            name: process.name.as_str(),
+            subname: Some(process.subnames[subidx].as_str()),
+            icon: None,
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.procs.iter().map(|p| p.search.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let content = "Name:\tfirefox\nUmask:\t0022\nState:\tS (sleeping)\n\
+                       Uid:\t1000\t1000\t1000\t1000\nVmRSS:\t  524288 kB\n";
+        assert_eq!(
+            parse_status(content),
+            Some(Status {
+                name: "firefox".into(),
+                uid: 1000,
+                rss: Some(524288),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_kernel_thread_status() {
+        let content = "Name:\tkworker/0:1\nUid:\t0\t0\t0\t0\n";
+        assert_eq!(parse_status(content).and_then(|s| s.rss), None);
+    }
+
+    #[test_case(512, "512 KiB")]
+    #[test_case(1536, "1.5 MiB")]
+    #[test_case(3 * 1024 * 1024, "3.0 GiB")]
+    fn test_format_size(kib: u64, expected: &str) {
+        assert_eq!(format_size(kib), expected);
+    }
+}
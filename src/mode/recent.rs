@@ -0,0 +1,292 @@
+use std::ffi::CString;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+
+use super::{Entry, EvalInfo, Mode};
+use crate::DesktopEntry;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Application {
+    name: String,
+    // command with `%u`/`%f` placeholders, e.g. `'gedit %u'`
+    exec: String,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Bookmark {
+    href: String,
+    time: Option<SystemTime>,
+    // the latest registered application
+    app: Option<Application>,
+}
+
+fn parse_time(node: roxmltree::Node<'_, '_>, attr: &str) -> Option<SystemTime> {
+    humantime::parse_rfc3339_weak(node.attribute(attr)?).ok()
+}
+
+// See <https://www.freedesktop.org/wiki/Specifications/desktop-bookmark-spec/>.
+fn parse_xbel(content: &str) -> Result<Vec<Bookmark>> {
+    let doc = roxmltree::Document::parse(content).context("invalid xbel file")?;
+
+    Ok(doc
+        .root_element()
+        .children()
+        .filter(|n| n.has_tag_name("bookmark"))
+        .filter_map(|node| {
+            let app = node
+                .descendants()
+                .filter(|n| n.tag_name().name() == "application")
+                .filter_map(|n| {
+                    Some(Application {
+                        name: n.attribute("name")?.to_owned(),
+                        exec: n.attribute("exec").unwrap_or_default().to_owned(),
+                        modified: parse_time(n, "modified"),
+                    })
+                })
+                .max_by_key(|a| a.modified);
+
+            Some(Bookmark {
+                href: node.attribute("href")?.to_owned(),
+                time: parse_time(node, "modified").max(parse_time(node, "visited")),
+                app,
+            })
+        })
+        .collect())
+}
+
+// Application names are either desktop file ids or plain names.
+fn find_desktop_entry(entries: &[DesktopEntry], app: &Application) -> Option<usize> {
+    let desktop_fname = format!("{}.desktop", app.name);
+    let binary = app
+        .exec
+        .trim_matches('\'')
+        .split_whitespace()
+        .next()
+        .unwrap_or_default();
+
+    entries
+        .iter()
+        .position(|e| e.desktop_fname == desktop_fname)
+        .or_else(|| {
+            entries
+                .iter()
+                .position(|e| e.name.eq_ignore_ascii_case(&app.name))
+        })
+        .or_else(|| {
+            entries.iter().position(|e| {
+                e.entry.exec.split_whitespace().next() == Some(binary) && !binary.is_empty()
+            })
+        })
+}
+
+// Directory with the home one shortened to `~`.
+fn display_dir(dir: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| dir.strip_prefix(home).ok()) {
+        Some(rel) if rel.as_os_str().is_empty() => "~".to_owned(),
+        Some(rel) => format!("~/{}", rel.display()),
+        None => dir.display().to_string(),
+    }
+}
+
+struct Recent {
+    name: String,
+    uri: String,
+    // parent directory, so same named files are told apart, and app name
+    subname: Option<String>,
+    // index of the registered app desktop entry
+    app: Option<usize>,
+}
+
+pub struct RecentMode {
+    recents: Vec<Recent>,
+    entries: Vec<DesktopEntry>,
+    term: Vec<CString>,
+}
+
+impl RecentMode {
+    pub fn new(entries: Vec<DesktopEntry>, term: Vec<CString>) -> Self {
+        let path = crate::desktop::xdg_dirs()
+            .get_data_home()
+            .join("recently-used.xbel");
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                log::warn!("cannot read {}: {e}", path.display());
+                String::new()
+            }
+        };
+
+        let mut bookmarks = if content.is_empty() {
+            vec![]
+        } else {
+            // Other apps may write it, so don't fail on it being broken.
+            parse_xbel(&content).unwrap_or_else(|e| {
+                log::warn!("{}: {e:#}", path.display());
+                vec![]
+            })
+        };
+        bookmarks.sort_by_key(|b| std::cmp::Reverse(b.time));
+        let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+
+        let recents = bookmarks
+            .into_iter()
+            .filter_map(|b| {
+                let local = crate::uri::file_path(&b.href);
+                // Removed files are still listed there.
+                if local.as_ref().is_some_and(|p| !p.exists()) {
+                    return None;
+                }
+
+                let name = match &local {
+                    Some(path) => path.file_name()?.to_string_lossy().into_owned(),
+                    None => b.href.clone(),
+                };
+                let app = b.app.as_ref().and_then(|a| find_desktop_entry(&entries, a));
+                let app_name = app
+                    .map(|idx| entries[idx].name.clone())
+                    .or_else(|| b.app.map(|a| a.name));
+                let dir = local
+                    .as_deref()
+                    .and_then(Path::parent)
+                    .map(|dir| display_dir(dir, home.as_deref()));
+                let subname = match (dir, app_name) {
+                    (Some(dir), Some(app)) => Some(format!("{dir} · {app}")),
+                    (dir, app) => dir.or(app),
+                };
+
+                Some(Recent {
+                    name,
+                    subname,
+                    uri: b.href,
+                    app,
+                })
+            })
+            .collect();
+
+        Self {
+            recents,
+            entries,
+            term,
+        }
+    }
+}
+
+impl Mode for RecentMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let idx = info.index.context("no recent file remain to open")?;
+        let recent = &self.recents[idx];
+        let file = crate::uri::file_path(&recent.uri)
+            .and_then(|p| p.to_str().map(str::to_owned))
+            .unwrap_or_else(|| recent.uri.clone());
+
+        let (term, args) = match recent.app.map(|app| &self.entries[app]) {
+            Some(entry) => (
+                entry.is_terminal.then(|| std::mem::take(&mut self.term)),
+                crate::desktop::expand_exec(&entry.entry.exec, &[&file])?,
+            ),
+            None => (None, vec!["xdg-open".to_owned(), file]),
+        };
+        let args = args
+            .into_iter()
+            .map(|s| CString::new(s).expect("invalid argument"));
+
+        crate::exec::exec(term, args, info.input_value).map(|x| match x {})
+    }
+
+    fn entries_len(&self) -> usize {
+        self.recents.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, idx: usize) -> bool {
+        self.recents[idx].subname.is_some()
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let recent = &self.recents[idx];
+
+        Entry {
+            name: recent.name.as_str(),
+            subname: recent.subname.as_deref(),
+            icon: recent
+                .app
+                .and_then(|app| self.entries[app].entry.icon.as_ref())
+                .and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.recents.iter().map(|r| r.name.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("/home/user/docs", "~/docs"; "in home")]
+    #[test_case("/home/user", "~"; "home itself")]
+    #[test_case("/home/username", "/home/username"; "same prefix")]
+    #[test_case("/tmp", "/tmp"; "outside home")]
+    fn test_display_dir(dir: &str, expected: &str) {
+        assert_eq!(
+            display_dir(Path::new(dir), Some(Path::new("/home/user"))),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_xbel() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info">
+  <bookmark href="file:///tmp/a.txt" added="2024-01-01T10:00:00Z"
+            modified="2024-01-02T10:00:00Z" visited="2024-01-03T10:00:00.123456Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;"
+                                modified="2024-01-02T10:00:00Z" count="1"/>
+          <bookmark:application name="org.gnome.TextEditor"
+                                exec="&apos;gnome-text-editor %u&apos;"
+                                modified="2024-01-03T10:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="https://example.com/"/>
+</xbel>"#;
+
+        let bookmarks = parse_xbel(content).unwrap();
+        assert_eq!(
+            bookmarks,
+            [
+                Bookmark {
+                    href: "file:///tmp/a.txt".into(),
+                    time: humantime::parse_rfc3339_weak("2024-01-03T10:00:00.123456Z").ok(),
+                    app: Some(Application {
+                        name: "org.gnome.TextEditor".into(),
+                        exec: "'gnome-text-editor %u'".into(),
+                        modified: humantime::parse_rfc3339_weak("2024-01-03T10:00:00Z").ok(),
+                    }),
+                },
+                Bookmark {
+                    href: "https://example.com/".into(),
+                    time: None,
+                    app: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, ensure, Context, Result};
+
+use super::{Entry, EvalInfo, Mode};
+use crate::desktop::IconLookup;
+use crate::icon::Icon;
+
+// Entry options are appended to the name as `\0key\x1fvalue\x1fkey\x1fvalue...`.
+const OPTIONS_SEPARATOR: char = '\0';
+const FIELD_SEPARATOR: char = '\x1f';
+
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+struct RawEntry<'a> {
+    name: &'a str,
+    subname: Option<&'a str>,
+    icon: Option<&'a str>,
+    info: Option<&'a str>,
+}
+
+fn parse_line(line: &str) -> RawEntry<'_> {
+    let (name, options) = line.split_once(OPTIONS_SEPARATOR).unwrap_or((line, ""));
+    let mut entry = RawEntry {
+        name,
+        ..Default::default()
+    };
+
+    // Only a trailing separator is ignored, empty values keep keys paired.
+    let mut fields = options.split_terminator(FIELD_SEPARATOR);
+    while let Some(key) = fields.next() {
+        let Some(value) = fields.next() else {
+            log::warn!("option `{key}` of entry `{name}` has no value");
+            break;
+        };
+        if value.is_empty() {
+            continue;
+        }
+
+        match key {
+            "subname" => entry.subname = Some(value),
+            "icon" => entry.icon = Some(value),
+            "info" => entry.info = Some(value),
+            _ => log::debug!("unknown option `{key}` of entry `{name}`, skipping"),
+        }
+    }
+
+    entry
+}
+
+struct ScriptEntry {
+    name: String,
+    subname: Option<String>,
+    icon: Option<Icon>,
+    info: Option<String>,
+}
+
+pub struct ScriptMode {
+    command: Vec<String>,
+    entries: Vec<ScriptEntry>,
+    icon_lookup: Option<IconLookup>,
+}
+
+impl ScriptMode {
+    pub fn new(command: Vec<String>, icon_lookup: Option<IconLookup>) -> Result<Self> {
+        ensure!(!command.is_empty(), "empty script command");
+
+        let mut mode = Self {
+            command,
+            entries: vec![],
+            icon_lookup,
+        };
+        mode.entries = mode.run(None)?;
+        Ok(mode)
+    }
+
+    fn run(&self, selected: Option<&str>) -> Result<Vec<ScriptEntry>> {
+        let (prog, args) = self.command.split_first().expect("checked in constructor");
+        let output = Command::new(prog)
+            .args(args)
+            .args(selected)
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()
+            .with_context(|| format!("failed to run script {prog}"))?;
+
+        if !output.status.success() {
+            bail!("script {prog} failed: {}", output.status);
+        }
+
+        let stdout = String::from_utf8(output.stdout).context("script output is not UTF-8")?;
+
+        Ok(stdout
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|line| {
+                let raw = parse_line(line);
+                ScriptEntry {
+                    name: raw.name.to_owned(),
+                    subname: raw.subname.map(str::to_owned),
+                    icon: raw
+                        .icon
+                        .zip(self.icon_lookup.as_ref())
+                        .and_then(|(name, lookup)| lookup.find_icon(name)),
+                    info: raw.info.map(str::to_owned),
+                }
+            })
+            .collect())
+    }
+}
+
+impl Mode for ScriptMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let selected = if let Some(idx) = info.index {
+            let entry = &self.entries[idx];
+            entry.info.as_deref().unwrap_or(&entry.name)
+        } else {
+            info.input_value.source
+        };
+
+        let entries = self.run(Some(selected))?;
+        if entries.is_empty() {
+            std::process::exit(0);
+        }
+        self.entries = entries;
+
+        Ok(())
+    }
+
+    fn entries_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, idx: usize) -> bool {
+        self.entries[idx].subname.is_some()
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let entry = &self.entries[idx];
+
+        Entry {
+            name: entry.name.as_str(),
+            subname: entry.subname.as_deref(),
+            icon: entry.icon.as_ref().and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.entries.iter().map(|e| e.name.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("plain", RawEntry { name: "plain", ..Default::default() }; "plain name")]
+    #[test_case("name\0subname\x1fsub", RawEntry {
+        name: "name",
+        subname: Some("sub"),
+        ..Default::default()
+    }; "with subname")]
+    #[test_case("name\0icon\x1ffirefox\x1finfo\x1fid-1\x1fsubname\x1fsub", RawEntry {
+        name: "name",
+        subname: Some("sub"),
+        icon: Some("firefox"),
+        info: Some("id-1"),
+    }; "all options")]
+    #[test_case("name\0unknown\x1fx\x1ficon\x1f/a/b.png", RawEntry {
+        name: "name",
+        icon: Some("/a/b.png"),
+        ..Default::default()
+    }; "unknown option skipped")]
+    #[test_case("name\0icon", RawEntry { name: "name", ..Default::default() }; "dangling key")]
+    #[test_case("name\0icon\x1f\x1fsubname\x1fx", RawEntry {
+        name: "name",
+        subname: Some("x"),
+        ..Default::default()
+    }; "empty value")]
+    #[test_case("name\0info\x1fid-1\x1f", RawEntry {
+        name: "name",
+        info: Some("id-1"),
+        ..Default::default()
+    }; "trailing separator")]
+    fn test_parse_line(line: &str, expected: RawEntry) {
+        assert_eq!(parse_line(line), expected);
+    }
+}
@@ -0,0 +1,410 @@
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use super::{Entry, EvalInfo, Mode};
+use crate::usage_cache::Usage;
+
+const CACHE_PATH: &str = concat!(crate::prog_name!(), ".sshcache");
+
+// Same limit as ssh itself uses for nested `Include`s.
+const MAX_INCLUDE_DEPTH: u8 = 16;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Host {
+    // `host` or `host:port` for non-default ports
+    name: String,
+    port: Option<String>,
+    hostname: Option<String>,
+}
+
+impl Host {
+    fn new(host: &str, port: Option<&str>) -> Self {
+        Self {
+            name: match port {
+                Some(port) => format!("{host}:{port}"),
+                None => host.to_owned(),
+            },
+            port: port.map(str::to_owned),
+            hostname: None,
+        }
+    }
+
+    fn host(&self) -> &str {
+        match &self.port {
+            Some(port) => &self.name[..self.name.len() - port.len() - 1],
+            None => &self.name,
+        }
+    }
+
+    fn ssh_args(&self) -> Vec<&str> {
+        match &self.port {
+            Some(port) => vec!["-p", port, self.host()],
+            None => vec![self.name.as_str()],
+        }
+    }
+
+    // Name as written to known_hosts.
+    fn known_hosts_name(&self) -> String {
+        match &self.port {
+            Some(port) => format!("[{}]:{port}", self.host()),
+            None => self.name.clone(),
+        }
+    }
+}
+
+// Known host hashed with `HashKnownHosts`, as `|1|salt|hash` in base64.
+struct HashedHost {
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl HashedHost {
+    fn parse(entry: &str) -> Option<Self> {
+        let (salt, hash) = entry.strip_prefix("|1|")?.split_once('|')?;
+        Some(Self {
+            salt: BASE64.decode(salt).ok()?,
+            hash: BASE64.decode(hash).ok()?,
+        })
+    }
+
+    fn matches(&self, host: &Host) -> bool {
+        let mut mac = Hmac::<Sha1>::new_from_slice(&self.salt).expect("any key size is valid");
+        mac.update(host.known_hosts_name().as_bytes());
+        mac.verify_slice(&self.hash).is_ok()
+    }
+}
+
+fn is_pattern(host: &str) -> bool {
+    host.contains(['*', '?', '!'])
+}
+
+// Splits `Keyword args` or `Keyword=args` line into the lowercased keyword
+// and its arguments.
+fn split_config_line(line: &str) -> Option<(String, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let end = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let (keyword, args) = line.split_at(end);
+    let args = args.trim_start();
+    let args = args.strip_prefix('=').unwrap_or(args).trim_start();
+    Some((keyword.to_ascii_lowercase(), args))
+}
+
+/// Collects `Host` aliases into `hosts`, returns patterns of included files.
+fn parse_config(content: &str, hosts: &mut Vec<Host>) -> Vec<String> {
+    let mut includes = vec![];
+    // Range of hosts from the last `Host` line, `HostName` applies to them.
+    let mut block = hosts.len()..hosts.len();
+
+    for (keyword, args) in content.lines().filter_map(split_config_line) {
+        match keyword.as_str() {
+            "host" => {
+                let start = hosts.len();
+                hosts.extend(
+                    shlex::split(args)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|h| !is_pattern(h))
+                        .map(|h| Host::new(&h, None)),
+                );
+                block = start..hosts.len();
+            }
+            "match" => block = hosts.len()..hosts.len(),
+            "hostname" => {
+                for host in &mut hosts[block.clone()] {
+                    host.hostname.get_or_insert_with(|| args.to_owned());
+                }
+            }
+            "include" => includes.extend(shlex::split(args).unwrap_or_default()),
+            _ => {}
+        }
+    }
+
+    includes
+}
+
+/// Returns plain hosts, hashed ones are collected into `hashed`.
+fn parse_known_hosts_line(line: &str, hashed: &mut Vec<HashedHost>) -> Vec<Host> {
+    let line = line.trim();
+    // Markers are used for CA keys and revoked keys, not for the hosts.
+    if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+        return vec![];
+    }
+
+    let Some((hosts, _)) = line.split_once(char::is_whitespace) else {
+        return vec![];
+    };
+
+    hashed.extend(hosts.split(',').filter_map(HashedHost::parse));
+
+    hosts
+        .split(',')
+        .filter(|h| !h.starts_with('|') && !is_pattern(h))
+        .map(
+            |h| match h.strip_prefix('[').and_then(|h| h.split_once("]:")) {
+                Some((host, "22")) => Host::new(host, None),
+                Some((host, port)) => Host::new(host, Some(port)),
+                None => Host::new(h, None),
+            },
+        )
+        .collect()
+}
+
+// Resolves the `Include` argument, only the file name may contain wildcards.
+fn expand_include(ssh_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let path = match pattern.strip_prefix("~/") {
+        Some(rest) => ssh_dir.parent().unwrap_or(ssh_dir).join(rest),
+        None => ssh_dir.join(pattern),
+    };
+
+    let Some(fname) = path.file_name().and_then(|f| f.to_str()) else {
+        return vec![];
+    };
+    if !is_pattern(fname) {
+        return vec![path];
+    }
+
+    let dir = path.parent().unwrap_or(ssh_dir);
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| log::debug!("cannot read {}: {e}", dir.display()))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|f| f.to_str())
+                .is_some_and(|f| crate::glob::matches(fname, f))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+// Hashed hosts cannot be turned back into names, so they are only recognized
+// among the names known from ssh config and plain known hosts.
+fn match_hashed(hashed: &[HashedHost], hosts: &[Host]) -> Vec<Host> {
+    let candidates = hosts.iter().flat_map(|h| {
+        let hostname = h.hostname.as_deref().map(|n| Host::new(n, None));
+        std::iter::once(Host::new(h.host(), h.port.as_deref())).chain(hostname)
+    });
+
+    candidates
+        .filter(|c| hashed.iter().any(|h| h.matches(c)))
+        .collect()
+}
+
+fn read_config(ssh_dir: &Path, path: &Path, depth: u8, hosts: &mut Vec<Host>) {
+    if depth > MAX_INCLUDE_DEPTH {
+        log::warn!("too deep ssh config includes at {}", path.display());
+        return;
+    }
+
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            log::debug!("cannot read ssh config {}: {e}", path.display());
+            return;
+        }
+    };
+
+    for include in parse_config(&content, hosts) {
+        for path in expand_include(ssh_dir, &include) {
+            read_config(ssh_dir, &path, depth + 1, hosts);
+        }
+    }
+}
+
+pub struct SshMode {
+    hosts: Vec<Host>,
+    term: Vec<CString>,
+    usage: Usage,
+}
+
+impl SshMode {
+    pub fn new(term: Vec<CString>) -> Self {
+        let usage = Usage::from_path(CACHE_PATH);
+
+        let ssh_dir = std::env::var_os("HOME")
+            .map(|home| Path::new(&home).join(".ssh"))
+            .unwrap_or_default();
+
+        let mut hosts = vec![];
+        read_config(&ssh_dir, &ssh_dir.join("config"), 0, &mut hosts);
+
+        let mut hashed = vec![];
+        match std::fs::read_to_string(ssh_dir.join("known_hosts")) {
+            Ok(content) => hosts.extend(
+                content
+                    .lines()
+                    .flat_map(|l| parse_known_hosts_line(l, &mut hashed)),
+            ),
+            Err(e) => log::debug!("cannot read known_hosts: {e}"),
+        }
+        let matched = match_hashed(&hashed, &hosts);
+        hosts.extend(matched);
+
+        // Keep the first occurrence, so config aliases win over known hosts.
+        let mut seen = std::collections::HashSet::new();
+        hosts.retain(|h| seen.insert(h.name.clone()));
+        hosts.sort_by_key(|h| std::cmp::Reverse(usage.entry_count(&h.name)));
+
+        Self { hosts, term, usage }
+    }
+}
+
+impl Mode for SshMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let custom;
+        let host = if let Some(idx) = info.index {
+            &self.hosts[idx]
+        } else {
+            custom = Host::new(info.search_string, None);
+            &custom
+        };
+
+        self.usage.increment_entry_usage(host.name.clone());
+        self.usage.try_update_cache(CACHE_PATH);
+
+        let command = std::iter::once("ssh")
+            .chain(host.ssh_args())
+            .map(CString::new)
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid host name")?;
+
+        crate::exec::exec(
+            Some(std::mem::take(&mut self.term)),
+            command,
+            info.input_value,
+        )
+        .map(|x| match x {})
+    }
+
+    fn entries_len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, idx: usize) -> bool {
+        self.hosts[idx].hostname.is_some()
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let host = &self.hosts[idx];
+
+        Entry {
+            name: host.name.as_str(),
+            subname: host.hostname.as_deref(),
+            icon: None,
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.hosts.iter().map(|h| h.name.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = "\
+# comment
+Host web web.example.com *.internal
+    HostName 10.0.0.1
+    User admin
+Host=db
+  hostname=db.example.com
+Match host foo
+  HostName ignored
+Host !bastion ?x
+Include config.d/* \"other config\"
+";
+        let mut hosts = vec![];
+        let includes = parse_config(config, &mut hosts);
+
+        assert_eq!(
+            hosts,
+            [
+                Host {
+                    name: "web".into(),
+                    port: None,
+                    hostname: Some("10.0.0.1".into()),
+                },
+                Host {
+                    name: "web.example.com".into(),
+                    port: None,
+                    hostname: Some("10.0.0.1".into()),
+                },
+                Host {
+                    name: "db".into(),
+                    port: None,
+                    hostname: Some("db.example.com".into()),
+                },
+            ]
+        );
+        assert_eq!(includes, ["config.d/*", "other config"]);
+    }
+
+    #[test_case("example.com ssh-ed25519 AAAA", &["example.com"]; "plain")]
+    #[test_case("a.com,10.0.0.1 ssh-rsa AAAA", &["a.com", "10.0.0.1"]; "multiple")]
+    #[test_case("[a.com]:2222 ssh-rsa AAAA", &["a.com:2222"]; "port")]
+    #[test_case("[a.com]:22 ssh-rsa AAAA", &["a.com"]; "default port")]
+    #[test_case("[::1]:2222,::1 ssh-rsa AAAA", &["::1:2222", "::1"]; "ipv6")]
+    #[test_case("|1|c2FsdA==|aGFzaA== ssh-rsa AAAA", &[]; "hashed")]
+    #[test_case("a.com,|1|c2FsdA==|aGFzaA== ssh-rsa AAAA", &["a.com"]; "hashed and plain")]
+    #[test_case("*.a.com ssh-rsa AAAA", &[]; "wildcard")]
+    #[test_case("@revoked a.com ssh-rsa AAAA", &[]; "marker")]
+    #[test_case("# a.com ssh-rsa AAAA", &[]; "comment")]
+    fn test_parse_known_hosts_line(line: &str, expected: &[&str]) {
+        let hosts = parse_known_hosts_line(line, &mut vec![]);
+        assert_eq!(
+            hosts.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_match_hashed() {
+        let known_hosts = "\
+|1|MDEyMzQ1Njc4OWFiY2RlZjAxMjM=|qpn8vvxybWkVYyAQZWbFYSByWCU= ssh-rsa AAAA
+|1|MDEyMzQ1Njc4OWFiY2RlZjAxMjM=|PLucHS+TbO21zXoa04jbfl/azRA= ssh-rsa AAAA
+|1|MDEyMzQ1Njc4OWFiY2RlZjAxMjM=|8CUii7ZnAP4kXM5iRyvZjoLtw18= ssh-rsa AAAA
+|1|invalid|base64 ssh-rsa AAAA
+";
+        let mut hashed = vec![];
+        for line in known_hosts.lines() {
+            assert_eq!(parse_known_hosts_line(line, &mut hashed), []);
+        }
+        assert_eq!(hashed.len(), 3);
+
+        let mut db = Host::new("db", None);
+        db.hostname = Some("db.example.com".into());
+        let hosts = [
+            db,
+            Host::new("a.com", Some("2222")),
+            Host::new("b.com", None),
+        ];
+
+        let matched = match_hashed(&hashed, &hosts);
+        assert_eq!(
+            matched.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(),
+            ["db.example.com", "a.com:2222"]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+
+use super::{Entry, EvalInfo, Mode};
+use crate::window::{Toplevel, ToplevelState, Toplevels};
+use crate::DesktopEntry;
+
+fn state_name(state: ToplevelState) -> &'static str {
+    match state {
+        ToplevelState::Maximized => "maximized",
+        ToplevelState::Minimized => "minimized",
+        ToplevelState::Activated => "active",
+        ToplevelState::Fullscreen => "fullscreen",
+        _ => "unknown",
+    }
+}
+
+struct Item {
+    toplevel: Toplevel,
+    // app_id followed by states, as displayed
+    subname: String,
+    // title followed by app_id
+    search: String,
+    // index of the app desktop entry
+    app: Option<usize>,
+}
+
+pub struct ToplevelsMode {
+    toplevels: Toplevels,
+    items: Vec<Item>,
+    entries: Vec<DesktopEntry>,
+}
+
+impl ToplevelsMode {
+    /// Lists windows advertised via `wlr-foreign-toplevel-management`, which
+    /// arrive once the window is created.
+    pub fn new(toplevels: Toplevels, entries: Vec<DesktopEntry>) -> Self {
+        Self {
+            toplevels,
+            items: vec![],
+            entries,
+        }
+    }
+}
+
+impl Mode for ToplevelsMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let idx = info.index.context("no window remain to activate")?;
+        let handle = &self.items[idx].toplevel.handle;
+        let toplevels = self.toplevels.borrow();
+
+        if info.subindex == 0 {
+            toplevels.activate(handle)?;
+        } else {
+            toplevels.close(handle)?;
+        }
+        std::process::exit(0);
+    }
+
+    fn entries_len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        1
+    }
+
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
+        let item = &self.items[idx];
+
+        Entry {
+            name: item.toplevel.title.as_str(),
+            subname: Some(match subidx {
+                0 => item.subname.as_str(),
+                _ => "Close",
+            }),
+            icon: item
+                .app
+                .and_then(|app| self.entries[app].entry.icon.as_ref())
+                .and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.items.iter().map(|i| i.search.as_str()))
+    }
+
+    fn refresh(&mut self) {
+        let Some(mut toplevels) = self.toplevels.borrow_mut().take_changed() else {
+            return;
+        };
+        // The active window goes last, so the previous one is selected first.
+        toplevels.sort_by_key(|t| t.states.contains(&ToplevelState::Activated));
+
+        self.items = toplevels
+            .into_iter()
+            .map(|toplevel| {
+                let mut subname = toplevel.app_id.clone();
+                for &state in &toplevel.states {
+                    subname.push_str(" · ");
+                    subname.push_str(state_name(state));
+                }
+
+                Item {
+                    search: format!("{} {}", toplevel.title, toplevel.app_id),
+                    app: self
+                        .entries
+                        .iter()
+                        .position(|e| e.matches_app_id(&toplevel.app_id)),
+                    subname,
+                    toplevel,
+                }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,342 @@
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{Entry, EvalInfo, Mode};
+use crate::DesktopEntry;
+
+const MAGIC: &[u8] = b"i3-ipc";
+
+const RUN_COMMAND: u32 = 0;
+const GET_TREE: u32 = 4;
+
+/// Client of sway/i3 IPC, see `sway-ipc(7)`.
+struct Connection {
+    stream: UnixStream,
+}
+
+impl Connection {
+    fn open(path: &Path) -> Result<Self> {
+        let stream = UnixStream::connect(path)
+            .with_context(|| format!("cannot connect to {}", path.display()))?;
+        Ok(Self { stream })
+    }
+
+    fn from_env() -> Result<Self> {
+        let path = std::env::var_os("SWAYSOCK")
+            .or_else(|| std::env::var_os("I3SOCK"))
+            .context("neither SWAYSOCK nor I3SOCK is set")?;
+        Self::open(Path::new(&path))
+    }
+
+    // Messages are the magic string followed by the payload length and type,
+    // both in native byte order, and the payload itself.
+    fn request(&mut self, kind: u32, payload: &str) -> Result<Vec<u8>> {
+        let mut message = MAGIC.to_vec();
+        message.extend((payload.len() as u32).to_ne_bytes());
+        message.extend(kind.to_ne_bytes());
+        message.extend(payload.as_bytes());
+        self.stream
+            .write_all(&message)
+            .context("cannot send ipc message")?;
+
+        let mut header = [0; 14];
+        self.stream
+            .read_exact(&mut header)
+            .context("cannot read ipc reply")?;
+        anyhow::ensure!(header.starts_with(MAGIC), "invalid ipc reply");
+        let len = u32::from_ne_bytes(header[6..10].try_into().unwrap());
+        let reply_kind = u32::from_ne_bytes(header[10..].try_into().unwrap());
+        anyhow::ensure!(reply_kind == kind, "unexpected ipc reply type {reply_kind}");
+
+        let mut reply = vec![0; len as usize];
+        self.stream
+            .read_exact(&mut reply)
+            .context("cannot read ipc reply")?;
+        Ok(reply)
+    }
+
+    fn tree(&mut self) -> Result<Node> {
+        let reply = self.request(GET_TREE, "")?;
+        serde_json::from_slice(&reply).context("invalid ipc tree")
+    }
+
+    fn run_command(&mut self, command: &str) -> Result<()> {
+        #[derive(Deserialize)]
+        struct Outcome {
+            success: bool,
+            error: Option<String>,
+        }
+
+        let reply = self.request(RUN_COMMAND, command)?;
+        let outcomes: Vec<Outcome> =
+            serde_json::from_slice(&reply).context("invalid ipc command reply")?;
+        match outcomes.into_iter().find(|o| !o.success) {
+            Some(outcome) => Err(anyhow::anyhow!(
+                "`{command}` failed: {}",
+                outcome.error.as_deref().unwrap_or("unknown error")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct WindowProperties {
+    class: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    id: i64,
+    #[serde(rename = "type")]
+    kind: String,
+    name: Option<String>,
+    // Wayland views only
+    app_id: Option<String>,
+    // X11 windows only
+    window: Option<u64>,
+    window_properties: Option<WindowProperties>,
+    #[serde(default)]
+    focused: bool,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    floating_nodes: Vec<Node>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Window {
+    con_id: i64,
+    title: String,
+    // app_id or X11 class
+    app_id: String,
+    workspace: String,
+    focused: bool,
+}
+
+fn collect_windows(node: Node, workspace: &str, windows: &mut Vec<Window>) {
+    let workspace = match node.kind.as_str() {
+        "workspace" => node.name.clone().unwrap_or_default(),
+        _ => workspace.to_owned(),
+    };
+
+    let is_window = node.app_id.is_some() || node.window.is_some();
+    if is_window && node.nodes.is_empty() {
+        let app_id = node
+            .app_id
+            .or_else(|| node.window_properties.and_then(|p| p.class))
+            .unwrap_or_default();
+        windows.push(Window {
+            con_id: node.id,
+            title: node.name.unwrap_or_default(),
+            app_id,
+            workspace,
+            focused: node.focused,
+        });
+        return;
+    }
+
+    for child in node.nodes.into_iter().chain(node.floating_nodes) {
+        collect_windows(child, &workspace, windows);
+    }
+}
+
+struct Item {
+    window: Window,
+    // as displayed
+    subname: String,
+    // title followed by app_id and workspace
+    search: String,
+    // index of the app desktop entry
+    app: Option<usize>,
+}
+
+pub struct WindowsMode {
+    items: Vec<Item>,
+    entries: Vec<DesktopEntry>,
+    connection: Connection,
+}
+
+impl WindowsMode {
+    /// Lists windows of the sway/i3 session, the focused one goes last.
+    pub fn new(entries: Vec<DesktopEntry>) -> Result<Self> {
+        Self::with_connection(Connection::from_env()?, entries)
+    }
+
+    fn with_connection(mut connection: Connection, entries: Vec<DesktopEntry>) -> Result<Self> {
+        let mut windows = vec![];
+        collect_windows(connection.tree()?, "", &mut windows);
+        windows.sort_by_key(|w| w.focused);
+
+        let items = windows
+            .into_iter()
+            .map(|window| Item {
+                subname: format!("{} · workspace {}", window.app_id, window.workspace),
+                search: format!("{} {} {}", window.title, window.app_id, window.workspace),
+                app: entries
+                    .iter()
+                    .position(|e| e.matches_app_id(&window.app_id)),
+                window,
+            })
+            .collect();
+
+        Ok(Self {
+            items,
+            entries,
+            connection,
+        })
+    }
+}
+
+impl Mode for WindowsMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> Result<()> {
+        let idx = info.index.context("no window remain to focus")?;
+        let con_id = self.items[idx].window.con_id;
+        self.connection
+            .run_command(&format!("[con_id={con_id}] focus"))?;
+        std::process::exit(0);
+    }
+
+    fn entries_len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn has_subname(&self, _: usize) -> bool {
+        true
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        let item = &self.items[idx];
+
+        Entry {
+            name: item.window.title.as_str(),
+            subname: Some(item.subname.as_str()),
+            icon: item
+                .app
+                .and_then(|app| self.entries[app].entry.icon.as_ref())
+                .and_then(|i| i.as_image()),
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.items.iter().map(|i| i.search.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixListener;
+    use std::sync::mpsc;
+
+    use super::*;
+
+    const TREE: &str = r#"{
+        "id": 1, "type": "root", "name": "root",
+        "nodes": [{
+            "id": 2, "type": "output", "name": "eDP-1",
+            "nodes": [{
+                "id": 3, "type": "workspace", "name": "1",
+                "nodes": [
+                    { "id": 4, "type": "con", "name": "yofi - Terminal", "app_id": "foot", "focused": true, "nodes": [] },
+                    {
+                        "id": 5, "type": "con", "name": null,
+                        "nodes": [
+                            { "id": 6, "type": "con", "name": "Mozilla Firefox", "app_id": null, "window": 42,
+                              "window_properties": { "class": "firefox" }, "nodes": [] }
+                        ]
+                    }
+                ],
+                "floating_nodes": [
+                    { "id": 7, "type": "floating_con", "name": "Calculator", "app_id": "org.gnome.Calculator", "nodes": [] }
+                ]
+            }]
+        }]
+    }"#;
+
+    fn window(con_id: i64, title: &str, app_id: &str, focused: bool) -> Window {
+        Window {
+            con_id,
+            title: title.into(),
+            app_id: app_id.into(),
+            workspace: "1".into(),
+            focused,
+        }
+    }
+
+    #[test]
+    fn test_collect_windows() {
+        let mut windows = vec![];
+        collect_windows(serde_json::from_str(TREE).unwrap(), "", &mut windows);
+        assert_eq!(
+            windows,
+            [
+                window(4, "yofi - Terminal", "foot", true),
+                window(6, "Mozilla Firefox", "firefox", false),
+                window(7, "Calculator", "org.gnome.Calculator", false),
+            ]
+        );
+    }
+
+    // Replies with the tree and a successful command result, sending the
+    // received messages back.
+    fn fake_server(path: &Path) -> mpsc::Receiver<(u32, String)> {
+        let listener = UnixListener::bind(path).unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut header = [0; 14];
+            while stream.read_exact(&mut header).is_ok() {
+                let len = u32::from_ne_bytes(header[6..10].try_into().unwrap());
+                let kind = u32::from_ne_bytes(header[10..].try_into().unwrap());
+                let mut payload = vec![0; len as usize];
+                stream.read_exact(&mut payload).unwrap();
+                tx.send((kind, String::from_utf8(payload).unwrap()))
+                    .unwrap();
+
+                let reply = match kind {
+                    GET_TREE => TREE,
+                    _ => r#"[{ "success": true }]"#,
+                };
+                let mut message = MAGIC.to_vec();
+                message.extend((reply.len() as u32).to_ne_bytes());
+                message.extend(kind.to_ne_bytes());
+                message.extend(reply.as_bytes());
+                stream.write_all(&message).unwrap();
+            }
+        });
+
+        rx
+    }
+
+    #[test]
+    fn test_ipc() {
+        let path = std::env::temp_dir().join(format!("yofi-ipc-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let messages = fake_server(&path);
+
+        let mut mode =
+            WindowsMode::with_connection(Connection::open(&path).unwrap(), vec![]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(messages.recv().unwrap(), (GET_TREE, String::new()));
+
+        let titles: Vec<_> = (0..mode.entries_len())
+            .map(|idx| mode.entry(idx, 0).name)
+            .collect();
+        assert_eq!(titles, ["Mozilla Firefox", "Calculator", "yofi - Terminal"]);
+
+        mode.connection.run_command("[con_id=6] focus").unwrap();
+        assert_eq!(
+            messages.recv().unwrap(),
+            (RUN_COMMAND, "[con_id=6] focus".into())
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use sctk::seat::keyboard::{Keysym, Modifiers};
+
 use crate::draw::ListItem;
 use crate::input_parser::InputValue;
-use crate::mode::{EvalInfo, Mode};
+use crate::mode::{calc, CalcMode, EvalInfo, Mode};
 pub use filtered_lines::ContinuousMatch;
 use filtered_lines::FilteredLines;
 
@@ -52,29 +54,129 @@ pub struct State {
     selected_item: usize,
     selected_subitem: usize,
     filtered_lines: FilteredLines,
-    inner: Mode,
+    // mode, search string and entries count of the last search while the mode
+    // is loading, so only appended entries are searched next time
+    searched_loading: Option<(usize, String, usize)>,
+    // labeled modes switchable at runtime, never empty
+    modes: Vec<(String, Box<dyn Mode>)>,
+    current_mode: usize,
+    // evaluates the input prefixed with `=`, its result is the top entry
+    calc: Option<CalcMode>,
 }
 
 impl State {
-    pub fn new(inner: Mode) -> Self {
+    pub fn new(inner: impl Mode + 'static) -> Self {
+        Self::with_modes(vec![(String::new(), Box::new(inner))])
+    }
+
+    pub fn with_modes(modes: Vec<(String, Box<dyn Mode>)>) -> Self {
+        assert!(!modes.is_empty(), "at least one mode is required");
+
         Self {
             input_buffer: InputBuffer::new(),
             skip_offset: 0,
             selected_item: 0,
             selected_subitem: 0,
-            filtered_lines: FilteredLines::unfiltred(inner.entries_len()),
-            inner,
+            filtered_lines: FilteredLines::unfiltred(modes[0].1.entries_len()),
+            searched_loading: None,
+            modes,
+            current_mode: 0,
+            calc: None,
         }
     }
 
+    pub fn enable_calc_prefix(&mut self) {
+        self.calc = Some(CalcMode::new());
+    }
+
+    /// Whether a single entry is left by the filter of the mode accepting it
+    /// right away and all entries are loaded.
+    pub fn should_auto_accept(&self) -> bool {
+        self.inner().auto_accepts() && !self.is_loading() && self.entries_len() == 1
+    }
+
+    // Calculator with a result to show above the mode entries.
+    fn calc(&self) -> Option<&CalcMode> {
+        self.calc
+            .as_ref()
+            .filter(|c| self.raw_input().starts_with(calc::PREFIX) && c.entries_len() > 0)
+    }
+
+    // Number of entries preceding the mode ones.
+    fn calc_offset(&self) -> usize {
+        usize::from(self.calc().is_some())
+    }
+
+    // Index of the selected mode entry, if any.
+    fn selected_index(&self) -> Option<usize> {
+        self.selected_item
+            .checked_sub(self.calc_offset())
+            .and_then(|item| self.filtered_lines.index(item))
+    }
+
+    fn inner(&self) -> &dyn Mode {
+        self.modes[self.current_mode].1.as_ref()
+    }
+
+    fn update_input(&mut self, f: impl FnOnce(&mut String)) {
+        self.input_buffer.update_input(f);
+        self.notify_input();
+    }
+
+    fn notify_input(&mut self) {
+        let input = self.input_buffer.parsed_input();
+        if let Some(calc) = &mut self.calc {
+            if input.source.starts_with(calc::PREFIX) {
+                calc.update_input(input);
+            }
+        }
+        self.modes[self.current_mode].1.update_input(input);
+    }
+
+    fn reset_selection(&mut self) {
+        self.skip_offset = 0;
+        self.selected_item = 0;
+        self.selected_subitem = 0;
+    }
+
+    pub fn next_mode(&mut self) {
+        self.current_mode = (self.current_mode + 1) % self.modes.len();
+        self.reset_selection();
+        self.notify_input();
+    }
+
+    pub fn prev_mode(&mut self) {
+        self.current_mode = self
+            .current_mode
+            .checked_sub(1)
+            .unwrap_or(self.modes.len() - 1);
+        self.reset_selection();
+        self.notify_input();
+    }
+
+    pub fn current_mode(&self) -> usize {
+        self.current_mode
+    }
+
+    pub fn mode_labels(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.modes.iter().map(|(label, _)| label.as_str())
+    }
+
     pub fn remove_input_char(&mut self) {
-        self.input_buffer.update_input(|input| {
+        if self.raw_input().is_empty() {
+            if self.modes[self.current_mode].1.back() {
+                self.reset_selection();
+            }
+            return;
+        }
+
+        self.update_input(|input| {
             input.pop();
         })
     }
 
     pub fn remove_input_word(&mut self) {
-        self.input_buffer.update_input(|input| {
+        self.update_input(|input| {
             if let Some(pos) = input.rfind(|x: char| !x.is_alphanumeric()) {
                 input.truncate(pos);
             } else {
@@ -84,33 +186,70 @@ impl State {
     }
 
     pub fn append_to_input(&mut self, s: &str) {
-        self.input_buffer.update_input(|input| input.push_str(s))
+        self.update_input(|input| input.push_str(s))
     }
 
     pub fn clear_input(&mut self) {
-        self.input_buffer.update_input(|input| input.clear())
+        self.update_input(|input| input.clear())
     }
 
     pub fn eval_input(&mut self, with_fork: bool) -> anyhow::Result<()> {
+        self.eval(with_fork, None)
+    }
+
+    /// Returns the exit code if the key is one of the mode accept keys.
+    pub fn accept_key(&self, keysym: Keysym, modifiers: Modifiers) -> Option<i32> {
+        self.inner()
+            .accept_keys()
+            .iter()
+            .find(|(chord, _)| chord.matches(keysym, modifiers))
+            .map(|&(_, exit_code)| exit_code)
+    }
+
+    pub fn cancel_exit_code(&self) -> Option<i32> {
+        self.inner().cancel_exit_code()
+    }
+
+    pub fn eval_accept_key(&mut self, exit_code: i32) -> anyhow::Result<()> {
+        self.eval(false, Some(exit_code))
+    }
+
+    fn eval(&mut self, with_fork: bool, exit_code: Option<i32>) -> anyhow::Result<()> {
+        let calc_selected = self.calc().is_some() && self.selected_item == 0;
         let info = EvalInfo {
-            index: self.filtered_lines.index(self.selected_item),
+            index: if calc_selected {
+                Some(0)
+            } else {
+                self.selected_index()
+            },
             subindex: self.selected_subitem,
             input_value: self.input_buffer.parsed_input(),
+            exit_code,
         };
+        let mode: &mut dyn Mode = match &mut self.calc {
+            // Keep the input to be fixed if there is no value.
+            Some(calc) if calc_selected && !calc.has_value() => return Ok(()),
+            Some(calc) if calc_selected => calc,
+            _ => self.modes[self.current_mode].1.as_mut(),
+        };
+        if info.index.is_none() && !mode.accepts_custom_input() {
+            return Ok(());
+        }
         if with_fork {
-            self.inner.fork_eval(info)
+            crate::mode::fork_eval(mode, info)
         } else {
-            self.inner
-                .eval(info)
-                .map(|_: std::convert::Infallible| unreachable!())
+            mode.eval(info)?;
+
+            // Mode is still running but with new entries, so start over.
+            self.clear_input();
+            self.reset_selection();
+            Ok(())
         }
     }
 
     pub fn next_item(&mut self) {
         self.selected_subitem = 0;
-        self.selected_item = self
-            .inner
-            .entries_len()
+        self.selected_item = (self.inner().entries_len() + self.calc_offset())
             .saturating_sub(1)
             .min(self.selected_item + 1);
     }
@@ -120,11 +259,21 @@ impl State {
         self.selected_item = self.selected_item.saturating_sub(1);
     }
 
+    /// Marks or unmarks the selected entry and moves to the next one.
+    pub fn toggle_mark(&mut self) {
+        let Some(idx) = self.selected_index() else {
+            return;
+        };
+        if self.modes[self.current_mode].1.toggle_mark(idx) {
+            self.next_item();
+        }
+    }
+
     pub fn next_subitem(&mut self) {
-        self.selected_subitem = self
-            .inner
-            .subentries_len(self.filtered_lines.index(self.selected_item).unwrap_or(0))
-            .min(self.selected_subitem + 1)
+        let subentries_len = self
+            .selected_index()
+            .map_or(0, |idx| self.inner().subentries_len(idx));
+        self.selected_subitem = subentries_len.min(self.selected_subitem + 1)
     }
 
     pub fn prev_subitem(&mut self) {
@@ -148,28 +297,80 @@ impl State {
     }
 
     pub fn selected_has_subitems(&self) -> bool {
-        self.filtered_lines
-            .index(self.selected_item)
-            .map(|idx| self.inner.subentries_len(idx))
-            .is_some_and(|count| count > 0)
+        match self.calc() {
+            Some(calc) if self.selected_item == 0 => calc.has_subname(0),
+            _ => self
+                .selected_index()
+                .is_some_and(|idx| self.inner().has_subname(idx)),
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.modes[self.current_mode].1.is_loading()
+    }
+
+    /// Number of the processed entries.
+    pub fn entries_len(&self) -> usize {
+        self.filtered_lines.len() + self.calc_offset()
     }
 
-    pub fn processed_entries(&self) -> impl ExactSizeIterator<Item = ListItem<'_>> {
-        self.filtered_lines
-            .list_items(&self.inner, self.selected_item, self.selected_subitem)
+    pub fn processed_entries(&self) -> impl Iterator<Item = ListItem<'_>> {
+        let calc = self.calc().map(|calc| {
+            let e = calc.entry(0, 0);
+            ListItem {
+                name: e.name,
+                subname: e.subname,
+                icon: e.icon,
+                match_mask: None,
+                marked: false,
+            }
+        });
+        let offset = self.calc_offset();
+        calc.into_iter().chain(self.filtered_lines.list_items(
+            self.inner(),
+            // selection is out of the mode entries if the calculator entry is selected
+            self.selected_item.wrapping_sub(offset),
+            self.selected_subitem,
+        ))
     }
 
     pub fn process_entries(&mut self) {
-        self.filtered_lines = if self.input_buffer.search_string().is_empty() {
-            FilteredLines::unfiltred(self.inner.entries_len())
+        let loading = self.is_loading();
+        self.modes[self.current_mode].1.refresh();
+
+        let search_string = self.input_buffer.search_string();
+        let len = self.inner().entries_len();
+        let searched = self
+            .searched_loading
+            .take()
+            .and_then(|(mode, search, len)| {
+                (mode == self.current_mode && search == search_string).then_some(len)
+            });
+        if search_string.is_empty() || !self.inner().is_searchable() {
+            self.filtered_lines = FilteredLines::unfiltred(len);
+        } else if let Some(searched) = searched {
+            let mut filtered_lines =
+                std::mem::replace(&mut self.filtered_lines, FilteredLines::unfiltred(0));
+            filtered_lines.extend_searched(
+                self.inner().text_entries().skip(searched),
+                searched,
+                search_string,
+            );
+            self.filtered_lines = filtered_lines;
         } else {
-            FilteredLines::searched(self.inner.text_entries(), self.input_buffer.search_string())
-        };
+            self.filtered_lines =
+                FilteredLines::searched(self.inner().text_entries(), search_string);
+        }
+        if loading {
+            self.searched_loading = Some((self.current_mode, search_string.to_owned(), len));
+        }
 
-        self.selected_item = self
-            .filtered_lines
-            .len()
-            .saturating_sub(1)
-            .min(self.selected_item);
+        let preselected = self.modes[self.current_mode].1.take_preselected();
+        if let Some(item) = preselected.and_then(|idx| self.filtered_lines.position(idx)) {
+            self.selected_item = item + self.calc_offset();
+            self.selected_subitem = 0;
+        }
+
+        self.selected_item = self.entries_len().saturating_sub(1).min(self.selected_item);
     }
 }
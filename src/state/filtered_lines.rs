@@ -24,6 +24,26 @@ impl FilteredLines {
         Self(Either::Left(v))
     }
 
+    /// Searches entries appended since the previous search, `entries` are
+    /// the new ones starting at the `offset`.
+    pub fn extend_searched<'a>(
+        &mut self,
+        entries: impl Iterator<Item = &'a str>,
+        offset: usize,
+        search_string: &str,
+    ) {
+        let Self(Either::Left(v)) = self else {
+            return;
+        };
+        v.extend(
+            entries.enumerate().filter_map(|(i, e)| {
+                Some((offset + i, sublime_fuzzy::best_match(search_string, e)?))
+            }),
+        );
+        // Stable sort keeps the order of equally scored entries, as on a full search.
+        v.sort_by(|(_, m1), (_, m2)| order_items(m1, m2));
+    }
+
     pub fn unfiltred(len: usize) -> Self {
         Self(Either::Right(len))
     }
@@ -50,9 +70,17 @@ impl FilteredLines {
         })
     }
 
+    /// Position of the entry among the filtered ones, if it's left.
+    pub fn position(&self, idx: usize) -> Option<usize> {
+        match self {
+            Self(Either::Left(x)) => x.iter().position(|(i, _)| *i == idx),
+            Self(Either::Right(x)) => (idx < *x).then_some(idx),
+        }
+    }
+
     pub fn list_items<'s, 'm: 's>(
         &'s self,
-        mode: &'m Mode,
+        mode: &'m dyn Mode,
         item: usize,
         subitem: usize,
     ) -> impl ExactSizeIterator<Item = ListItem<'_>> + '_ {
@@ -64,7 +92,10 @@ impl FilteredLines {
                         name: e.name,
                         subname: e.subname,
                         icon: e.icon,
-                        match_mask: Some(s_match.continuous_matches()),
+                        match_mask: mode
+                            .highlights_matches()
+                            .then(|| s_match.continuous_matches()),
+                        marked: mode.is_marked(*item_idx),
                     }
                 }))
             }
@@ -75,6 +106,7 @@ impl FilteredLines {
                     subname: e.subname,
                     icon: e.icon,
                     match_mask: None,
+                    marked: mode.is_marked(i),
                 }
             })),
         }
@@ -105,4 +137,20 @@ mod tests {
 
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn test_extend_searched() {
+        let entries = ["yx", "xy", "ab", "x-y", "xy"];
+        let full = FilteredLines::searched(entries.into_iter(), "xy");
+
+        let mut extended = FilteredLines::searched(entries[..2].iter().copied(), "xy");
+        extended.extend_searched(entries[2..].iter().copied(), 2, "xy");
+
+        let indices = |lines: &FilteredLines| {
+            (0..lines.len())
+                .map(|i| lines.index(i).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(indices(&extended), indices(&full));
+    }
 }
@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+pub fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let decoded = (b == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Local path of `file://` URI.
+pub fn file_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    path.starts_with('/').then(|| percent_decode(path).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("plain", "plain"; "plain")]
+    #[test_case("My%20Files", "My Files"; "space")]
+    #[test_case("%D0%AF", "Я"; "utf8")]
+    #[test_case("100%", "100%"; "trailing percent")]
+    #[test_case("%zz", "%zz"; "invalid hex")]
+    fn test_percent_decode(s: &str, expected: &str) {
+        assert_eq!(percent_decode(s), expected);
+    }
+
+    #[test_case("file:///a%20b/c", Some("/a b/c"); "local")]
+    #[test_case("file://host/a", None; "remote")]
+    #[test_case("https://a.b/", None; "not file")]
+    fn test_file_path(uri: &str, expected: Option<&str>) {
+        assert_eq!(file_path(uri), expected.map(PathBuf::from));
+    }
+}
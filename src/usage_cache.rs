@@ -26,8 +26,7 @@ impl Usage {
                         let line = l.map_err(|e| {
                             log::error!("unable to read the line from cache: {}", e)
                         })?;
-                        let mut iter = line.split(' ');
-                        let (count, entry) = (iter.next().ok_or(())?, iter.next().ok_or(())?);
+                        let (count, entry) = line.split_once(' ').ok_or(())?;
 
                         let count = count.parse().map_err(|e| {
                             log::error!(
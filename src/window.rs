@@ -25,16 +25,20 @@ use sctk::{
 };
 
 use crate::state::State;
+pub use lines::{InputLines, LineBuffer};
 pub use pointer::Params as PointerParams;
+pub use toplevel::{Toplevel, ToplevelList, ToplevelState, Toplevels};
 
 mod compositor;
 mod keyboard;
 mod layer_shell;
+mod lines;
 mod output;
 mod pointer;
 mod registry;
 mod seat;
 mod shm;
+mod toplevel;
 mod xdg_window;
 
 pub struct Params {
@@ -66,6 +70,9 @@ pub struct Window {
     key_modifiers: sctk::seat::keyboard::Modifiers,
     wheel_scroll_pending: f64,
 
+    // shared with the mode listing them, if any
+    toplevels: Option<Toplevels>,
+
     loop_handle: LoopHandle<'static, Window>,
     exit: bool,
 
@@ -100,9 +107,14 @@ impl std::ops::Deref for RenderSurface {
 }
 
 impl Window {
+    /// Toplevels are filled from the compositor once given, see
+    /// `zwlr_foreign_toplevel_manager_v1`. Lines of the inputs are read as they
+    /// arrive.
     pub fn new(
         config: crate::config::Config,
         state: State,
+        toplevels: Option<Toplevels>,
+        inputs: Vec<InputLines>,
     ) -> anyhow::Result<(Self, EventLoop<'static, Self>)> {
         let conn = Connection::connect_to_env()?;
 
@@ -111,7 +123,10 @@ impl Window {
         let event_loop: EventLoop<Window> =
             EventLoop::try_new().context("failed to initialize the event loop")?;
         let loop_handle = event_loop.handle();
-        WaylandSource::new(conn.clone(), event_queue).insert(loop_handle)?;
+        WaylandSource::new(conn.clone(), event_queue).insert(loop_handle.clone())?;
+        for input in &inputs {
+            Self::watch_input(input, &loop_handle)?;
+        }
 
         let params: Params = config.param();
         let scale = params.scale.unwrap_or(1);
@@ -123,6 +138,17 @@ impl Window {
         )
         .context("Failed to create a memory pool!")?;
 
+        let seat_state = SeatState::new(&globals, &qh);
+        if let Some(toplevels) = &toplevels {
+            // Other modes are still usable, so the list is just left empty.
+            if let Err(err) = Self::bind_toplevel_manager(toplevels, &conn, &globals, &qh) {
+                log::warn!("windows cannot be listed: {err}");
+            }
+            if let Some(seat) = seat_state.seats().next() {
+                toplevels.borrow_mut().set_seat(seat);
+            }
+        }
+
         let compositor = sctk::compositor::CompositorState::bind(&globals, &qh)
             .context("wl_compositor is not available")?;
         let surface = compositor.create_surface(&qh);
@@ -170,7 +196,7 @@ impl Window {
                 config,
                 state,
                 registry_state: RegistryState::new(&globals),
-                seat_state: SeatState::new(&globals, &qh),
+                seat_state,
                 output_state: OutputState::new(&globals, &qh),
                 buffer: None,
                 pool,
@@ -186,6 +212,7 @@ impl Window {
                 },
                 key_modifiers: Default::default(),
                 wheel_scroll_pending: 0.0,
+                toplevels,
                 loop_handle: event_loop.handle(),
                 exit: false,
                 error: None,
@@ -225,6 +252,13 @@ impl Window {
     }
 
     pub fn draw(&mut self, qh: &QueueHandle<Self>) {
+        // Entries are filtered on the previous draw.
+        if self.state.should_auto_accept() {
+            if let Err(err) = self.state.eval_input(false) {
+                self.error = Some(err);
+            }
+        }
+
         let width = self.width().try_into().expect("width overflow");
         let height = self.height().try_into().expect("height overflow");
         let stride = width * 4;
@@ -328,6 +362,10 @@ impl Window {
     pub fn take_error(&mut self) -> Option<anyhow::Error> {
         self.error.take()
     }
+
+    pub fn cancel_exit_code(&self) -> Option<i32> {
+        self.state.cancel_exit_code()
+    }
 }
 
 delegate_compositor!(Window);
@@ -12,10 +12,34 @@ impl Window {
     pub fn handle_keypress(&mut self, event: sctk::seat::keyboard::KeyEvent) {
         use sctk::seat::keyboard::Keysym;
         type M = Modifiers;
+        if let Some(exit_code) = self.state.accept_key(event.keysym, self.key_modifiers) {
+            if let Err(err) = self.state.eval_accept_key(exit_code) {
+                self.error = Some(err);
+            }
+            return;
+        }
+
         match (event.keysym, self.key_modifiers) {
             (Keysym::Escape, _) | (Keysym::c, M { ctrl: true, .. }) => {
                 self.exit = true;
             }
+            (
+                Keysym::Tab,
+                M {
+                    ctrl: true,
+                    shift: false,
+                    ..
+                },
+            ) => self.state.next_mode(),
+            (
+                Keysym::Tab,
+                M {
+                    ctrl: true,
+                    shift: true,
+                    ..
+                },
+            )
+            | (Keysym::ISO_Left_Tab, M { ctrl: true, .. }) => self.state.prev_mode(),
             (Keysym::Down, _)
             | (Keysym::j, M { ctrl: true, .. })
             | (Keysym::Tab, M { shift: false, .. })
@@ -24,6 +48,7 @@ impl Window {
             | (Keysym::k, M { ctrl: true, .. })
             | (Keysym::Tab, M { shift: true, .. })
             | (Keysym::ISO_Left_Tab, M { shift: true, .. }) => self.state.prev_item(),
+            (Keysym::space, M { ctrl: true, .. }) => self.state.toggle_mark(),
             (Keysym::Left, _) => self.state.prev_subitem(),
             (Keysym::Right, _) => self.state.next_subitem(),
             (Keysym::Return, M { ctrl, .. }) | (Keysym::ISO_Enter, M { ctrl, .. }) => {
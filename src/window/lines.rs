@@ -0,0 +1,200 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::os::fd::AsRawFd;
+use std::rc::Rc;
+
+use anyhow::Context;
+use nix::fcntl::{fcntl, FcntlArg, OFlag};
+use sctk::reexports::calloop::{
+    generic::Generic, Interest, LoopHandle, Mode as CallMode, PostAction,
+};
+
+use super::Window;
+
+// Reads are limited per wakeup, so a fast writer doesn't starve the loop.
+const CHUNK_SIZE: usize = 64 * 1024;
+const MAX_CHUNKS: usize = 16;
+
+/// Lines read from the input as they arrive, shared between the window, which
+/// polls the input, and the mode listing them.
+pub type InputLines = Rc<RefCell<LineBuffer>>;
+
+pub struct LineBuffer {
+    // taken by the window to poll it
+    input: Option<File>,
+    // `\n` or `\0` for NUL-separated records
+    separator: u8,
+    lines: Vec<String>,
+    // incomplete last line
+    partial: Vec<u8>,
+    eof: bool,
+}
+
+impl Default for LineBuffer {
+    fn default() -> Self {
+        Self::new(b'\n')
+    }
+}
+
+impl LineBuffer {
+    pub fn new(separator: u8) -> Self {
+        Self {
+            input: None,
+            separator,
+            lines: vec![],
+            partial: vec![],
+            eof: false,
+        }
+    }
+
+    /// Lines are read from the `input` once the window is created.
+    pub fn set_input(&mut self, input: File) {
+        self.input = Some(input);
+    }
+
+    /// Reads the blocking `input` till the end.
+    pub fn read_all(&mut self, input: impl Read) -> std::io::Result<()> {
+        self.read_from(input, usize::MAX)
+    }
+
+    /// Returns lines arrived since the last call.
+    pub fn take_lines(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.lines)
+    }
+
+    /// Whether the input is not read till the end or not all lines are taken.
+    pub fn is_loading(&self) -> bool {
+        !self.eof || !self.lines.is_empty()
+    }
+
+    // Reads until the input would block, ends or `max_chunks` are read.
+    fn read_from(&mut self, mut input: impl Read, max_chunks: usize) -> std::io::Result<()> {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        for _ in 0..max_chunks {
+            match input.read(&mut chunk) {
+                Ok(0) => {
+                    if !self.partial.is_empty() {
+                        let last = std::mem::take(&mut self.partial);
+                        self.push_line(last);
+                    }
+                    self.eof = true;
+                    break;
+                }
+                Ok(n) => self.push_bytes(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn push_bytes(&mut self, mut bytes: &[u8]) {
+        while let Some(pos) = bytes.iter().position(|&b| b == self.separator) {
+            let mut line = std::mem::take(&mut self.partial);
+            line.extend_from_slice(&bytes[..pos]);
+            self.push_line(line);
+            bytes = &bytes[pos + 1..];
+        }
+        self.partial.extend_from_slice(bytes);
+    }
+
+    // Same as `BufRead::lines`, `\r\n` is stripped as well. Invalid UTF-8
+    // (e.g. in file names) is replaced rather than failing the whole input.
+    fn push_line(&mut self, mut line: Vec<u8>) {
+        if self.separator == b'\n' && line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        };
+        self.lines.push(line);
+    }
+}
+
+impl Window {
+    pub(super) fn watch_input(
+        lines: &InputLines,
+        loop_handle: &LoopHandle<'static, Self>,
+    ) -> anyhow::Result<()> {
+        let Some(input) = lines.borrow_mut().input.take() else {
+            return Ok(());
+        };
+        let flags = fcntl(input.as_raw_fd(), FcntlArg::F_GETFL).context("input flags")?;
+        fcntl(
+            input.as_raw_fd(),
+            FcntlArg::F_SETFL(OFlag::from_bits_truncate(flags) | OFlag::O_NONBLOCK),
+        )
+        .context("non-blocking input")?;
+
+        let source = Generic::new(input, Interest::READ, CallMode::Level);
+        let shared = lines.clone();
+        let inserted = loop_handle.insert_source(source, move |_, input, window| {
+            let mut lines = shared.borrow_mut();
+            if let Err(err) = lines.read_from(&**input, MAX_CHUNKS) {
+                window.error = Some(anyhow::Error::new(err).context("failed to read input"));
+                return Ok(PostAction::Remove);
+            }
+            Ok(if lines.eof {
+                PostAction::Remove
+            } else {
+                PostAction::Continue
+            })
+        });
+
+        // Regular files (and e.g. `/dev/null`) can't be polled, but never block either.
+        if let Err(err) = inserted {
+            log::debug!("reading input at once, cannot poll it: {}", err.error);
+            let input = err.inserted.unwrap();
+            lines
+                .borrow_mut()
+                .read_all(&input)
+                .context("failed to read input")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_lines() {
+        let mut lines = LineBuffer::default();
+        lines.push_bytes(b"first\nsec");
+        assert_eq!(lines.take_lines(), ["first"]);
+
+        lines.push_bytes(b"ond\r\nthird");
+        assert_eq!(lines.take_lines(), ["second"]);
+        assert!(lines.is_loading());
+
+        lines.read_from(&b""[..], MAX_CHUNKS).unwrap();
+        assert_eq!(lines.take_lines(), ["third"]);
+        assert!(!lines.is_loading());
+    }
+
+    #[test]
+    fn test_read_until_eof() {
+        let mut lines = LineBuffer::default();
+        lines.read_from(&b"a\nb\n"[..], MAX_CHUNKS).unwrap();
+        assert_eq!(lines.take_lines(), ["a", "b"]);
+        assert!(!lines.is_loading());
+    }
+
+    #[test]
+    fn test_nul_separated() {
+        let mut lines = LineBuffer::new(b'\0');
+        lines.read_all(&b"multi\nline\r\0path\0"[..]).unwrap();
+        assert_eq!(lines.take_lines(), ["multi\nline\r", "path"]);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut lines = LineBuffer::new(b'\0');
+        lines.read_all(&b"/tmp/\xff.txt\0next\0"[..]).unwrap();
+        assert_eq!(lines.take_lines(), ["/tmp/\u{FFFD}.txt", "next"]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sctk::reexports::client::{
+    event_created_child, protocol::wl_seat::WlSeat, Connection, Dispatch, Proxy, QueueHandle,
+};
+use sctk::reexports::protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+use super::Window;
+
+pub use zwlr_foreign_toplevel_handle_v1::State as ToplevelState;
+
+/// Toplevels advertised by the compositor, shared between the window, which
+/// receives them, and the mode listing them.
+pub type Toplevels = Rc<RefCell<ToplevelList>>;
+
+#[derive(Clone)]
+pub struct Toplevel {
+    pub handle: ZwlrForeignToplevelHandleV1,
+    pub title: String,
+    pub app_id: String,
+    pub states: Vec<ToplevelState>,
+}
+
+#[derive(Default)]
+pub struct ToplevelList {
+    toplevels: Vec<Toplevel>,
+    // set on every `done` event, until the list is taken
+    changed: bool,
+    seat: Option<WlSeat>,
+    conn: Option<Connection>,
+}
+
+impl ToplevelList {
+    /// Returns toplevels if they were changed since the last call.
+    pub fn take_changed(&mut self) -> Option<Vec<Toplevel>> {
+        std::mem::take(&mut self.changed).then(|| self.toplevels.clone())
+    }
+
+    /// Focuses the toplevel with our seat.
+    pub fn activate(&self, handle: &ZwlrForeignToplevelHandleV1) -> anyhow::Result<()> {
+        let seat = self
+            .seat
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("no seat to activate the window with"))?;
+        handle.activate(seat);
+        self.flush()
+    }
+
+    pub fn close(&self, handle: &ZwlrForeignToplevelHandleV1) -> anyhow::Result<()> {
+        handle.close();
+        self.flush()
+    }
+
+    // Requests are sent right away as the process exits afterwards.
+    fn flush(&self) -> anyhow::Result<()> {
+        if let Some(conn) = &self.conn {
+            conn.flush()?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn set_seat(&mut self, seat: WlSeat) {
+        self.seat.get_or_insert(seat);
+    }
+
+    fn toplevel_mut(&mut self, handle: &ZwlrForeignToplevelHandleV1) -> Option<&mut Toplevel> {
+        self.toplevels.iter_mut().find(|t| &t.handle == handle)
+    }
+}
+
+impl Window {
+    pub(super) fn bind_toplevel_manager(
+        toplevels: &Toplevels,
+        conn: &Connection,
+        globals: &sctk::reexports::client::globals::GlobalList,
+        qh: &QueueHandle<Self>,
+    ) -> anyhow::Result<()> {
+        globals
+            .bind::<ZwlrForeignToplevelManagerV1, _, _>(qh, 1..=3, ())
+            .map_err(|e| {
+                anyhow::anyhow!("zwlr_foreign_toplevel_manager_v1 is not available: {e}")
+            })?;
+        toplevels.borrow_mut().conn = Some(conn.clone());
+        Ok(())
+    }
+
+    // Seats are bound on start, this one handles ones appeared later.
+    pub(super) fn set_toplevel_seat(&mut self, seat: &WlSeat) {
+        if let Some(toplevels) = &self.toplevels {
+            toplevels.borrow_mut().set_seat(seat.clone());
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for Window {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some(toplevels) = &state.toplevels else {
+            return;
+        };
+
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                toplevels.borrow_mut().toplevels.push(Toplevel {
+                    handle: toplevel,
+                    title: String::new(),
+                    app_id: String::new(),
+                    states: vec![],
+                });
+            }
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => {
+                log::debug!("toplevel manager is finished");
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(Window, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for Window {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some(toplevels) = &state.toplevels else {
+            return;
+        };
+        let mut toplevels = toplevels.borrow_mut();
+
+        if let zwlr_foreign_toplevel_handle_v1::Event::Closed = event {
+            toplevels.toplevels.retain(|t| &t.handle != proxy);
+            toplevels.changed = true;
+            proxy.destroy();
+            return;
+        }
+        if let zwlr_foreign_toplevel_handle_v1::Event::Done = event {
+            toplevels.changed = true;
+            return;
+        }
+
+        let Some(toplevel) = toplevels.toplevel_mut(proxy) else {
+            log::warn!("event for unknown toplevel {}", proxy.id());
+            return;
+        };
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => toplevel.title = title,
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => toplevel.app_id = app_id,
+            zwlr_foreign_toplevel_handle_v1::Event::State { state } => {
+                // Array of native-endian u32 values.
+                toplevel.states = state
+                    .chunks_exact(4)
+                    .filter_map(|c| {
+                        ToplevelState::try_from(u32::from_ne_bytes(c.try_into().ok()?)).ok()
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+}
@@ -8,13 +8,78 @@
 //! inspection. The diff highlights changed pixels in red.
 
 mod snap;
-use snap::{run_regression, test_entries, Action};
+use snap::{run_modes_regression, run_regression, run_state_regression, test_entries, Action};
+use yofi::mode::{DialogMode, DialogOptions, Entry, EvalInfo, Mode};
+use yofi::state::State;
+
+struct StaticMode(Vec<String>);
+
+impl Mode for StaticMode {
+    fn eval(&mut self, _: EvalInfo<'_>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn entries_len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn subentries_len(&self, _: usize) -> usize {
+        0
+    }
+
+    fn entry(&self, idx: usize, _: usize) -> Entry<'_> {
+        Entry {
+            name: &self.0[idx],
+            subname: None,
+            icon: None,
+        }
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.0.iter().map(String::as_str))
+    }
+}
+
+// Entries keep arriving, e.g. from a pipe.
+struct LoadingMode(StaticMode);
+
+impl Mode for LoadingMode {
+    fn eval(&mut self, info: EvalInfo<'_>) -> anyhow::Result<()> {
+        self.0.eval(info)
+    }
+
+    fn entries_len(&self) -> usize {
+        self.0.entries_len()
+    }
+
+    fn subentries_len(&self, idx: usize) -> usize {
+        self.0.subentries_len(idx)
+    }
+
+    fn entry(&self, idx: usize, subidx: usize) -> Entry<'_> {
+        self.0.entry(idx, subidx)
+    }
+
+    fn text_entries(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.0.text_entries()
+    }
+
+    fn is_loading(&self) -> bool {
+        true
+    }
+}
 
 #[test]
 fn initial() {
     run_regression("initial", test_entries(), &[]);
 }
 
+#[test]
+fn custom_mode() {
+    // Renders exactly as the dialog mode with the same entries.
+    run_state_regression("initial", State::new(StaticMode(test_entries())), &[]);
+}
+
 #[test]
 fn search() {
     run_regression("search", test_entries(), &[Action::Type("fire")]);
@@ -37,3 +102,79 @@ fn search_then_nav() {
         &[Action::Type("te"), Action::NextItem],
     );
 }
+
+#[test]
+fn multi_marks() {
+    let mode = DialogMode::with_options(
+        test_entries(),
+        DialogOptions {
+            multi: true,
+            ..Default::default()
+        },
+    );
+    run_state_regression(
+        "multi_marks",
+        State::new(mode),
+        &[Action::ToggleMark, Action::NextItem, Action::ToggleMark],
+    );
+}
+
+#[test]
+fn mode_tabs() {
+    run_modes_regression(
+        "mode_tabs",
+        vec![
+            ("first", test_entries()),
+            ("second", test_entries().into_iter().rev().collect()),
+            ("third", vec![]),
+        ],
+        &[Action::NextMode],
+    );
+}
+
+#[test]
+fn loading() {
+    run_state_regression(
+        "loading",
+        State::new(LoadingMode(StaticMode(test_entries()))),
+        &[Action::Type("fire")],
+    );
+}
+
+#[test]
+fn preselect() {
+    let mode = DialogMode::with_options(
+        test_entries(),
+        DialogOptions {
+            select: Some("Files".to_owned()),
+            ..Default::default()
+        },
+    );
+    run_state_regression("preselect", State::new(mode), &[Action::Type("i")]);
+}
+
+#[test]
+fn multi_line() {
+    let entries = vec![
+        "notes.txt\nshopping list\nmilk, bread".to_owned(),
+        "single line".to_owned(),
+    ];
+    let mode = DialogMode::with_options(
+        entries,
+        DialogOptions {
+            null: true,
+            ..Default::default()
+        },
+    );
+    run_state_regression("multi_line", State::new(mode), &[]);
+}
+
+#[test]
+fn calc_prefix() {
+    let mut state = State::new(StaticMode(vec![
+        "Calculator".to_owned(),
+        "x = 2 * 3".to_owned(),
+    ]));
+    state.enable_calc_prefix();
+    run_state_regression("calc_prefix", state, &[Action::Type("=2*3")]);
+}
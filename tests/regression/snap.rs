@@ -1,11 +1,13 @@
 use yofi::config::Config;
-use yofi::mode::Mode;
+use yofi::mode::{DialogMode, Mode};
 use yofi::state::State;
 use yofi::window::Params;
 
 pub enum Action {
     Type(&'static str),
     NextItem,
+    NextMode,
+    ToggleMark,
 }
 
 pub fn test_entries() -> Vec<String> {
@@ -85,15 +87,30 @@ fn load_png_rgba(path: &str) -> (u32, u32, Vec<u8>) {
 }
 
 pub fn run_regression(name: &str, entries: Vec<String>, actions: &[Action]) {
-    let config = Config::default();
+    let mode = DialogMode::from_lines(entries);
+    run_state_regression(name, State::new(mode), actions);
+}
 
-    let mode = Mode::dialog_from_lines(entries);
-    let mut state = State::new(mode);
+pub fn run_modes_regression(name: &str, modes: Vec<(&str, Vec<String>)>, actions: &[Action]) {
+    let modes = modes
+        .into_iter()
+        .map(|(label, entries)| {
+            let mode: Box<dyn Mode> = Box::new(DialogMode::from_lines(entries));
+            (label.to_string(), mode)
+        })
+        .collect();
+    run_state_regression(name, State::with_modes(modes), actions);
+}
+
+pub fn run_state_regression(name: &str, mut state: State, actions: &[Action]) {
+    let config = Config::default();
 
     for action in actions {
         match action {
             Action::Type(s) => state.append_to_input(s),
             Action::NextItem => state.next_item(),
+            Action::NextMode => state.next_mode(),
+            Action::ToggleMark => state.toggle_mark(),
         }
     }
 
@@ -0,0 +1,130 @@
+#!/usr/bin/env python3
+"""Generates the table of characters picked by the chars mode.
+
+    tools/chars_table.py emoji-test.txt UnicodeData.txt [annotations.json...] \
+        > src/mode/chars.tsv
+
+Sources:
+- https://unicode.org/Public/emoji/latest/emoji-test.txt for all fully
+  qualified emoji with their CLDR short names, skin tone variants are folded
+  into the `tone` flag of the base emoji;
+- https://unicode.org/Public/UCD/latest/ucd/UnicodeData.txt for non-emoji
+  characters of `BLOCKS`, named by their Unicode names;
+- optional CLDR annotations for keywords, e.g. `annotations/en/annotations.json`
+  and `annotationsDerived/en/annotations.json` of
+  https://github.com/unicode-org/cldr-json;
+- `chars_keywords.tsv` next to this script for extra keywords.
+"""
+
+import json
+import sys
+from pathlib import Path
+
+SKIN_TONES = [chr(c) for c in range(0x1F3FB, 0x1F400)]
+VARIATION_SELECTOR = "\ufe0f"
+
+# Blocks of non-emoji characters worth picking by name.
+BLOCKS = [
+    (0x00A0, 0x00BF),  # Latin-1 punctuation and symbols
+    (0x00D7, 0x00D7),
+    (0x00F7, 0x00F7),
+    (0x0391, 0x03C9),  # Greek letters
+    (0x2000, 0x205F),  # General Punctuation
+    (0x2070, 0x209C),  # Superscripts and Subscripts
+    (0x20A0, 0x20C0),  # Currency Symbols
+    (0x2100, 0x218B),  # Letterlike Symbols, Number Forms
+    (0x2190, 0x23FF),  # Arrows, Mathematical Operators, Miscellaneous Technical
+    (0x2460, 0x24FF),  # Enclosed Alphanumerics
+    (0x25A0, 0x27BF),  # Geometric Shapes, Miscellaneous Symbols, Dingbats
+    (0x27F0, 0x27FF),  # Supplemental Arrows-A
+    (0x2900, 0x297F),  # Supplemental Arrows-B
+]
+# Letters, numbers, punctuation, symbols and spaces, no marks or controls.
+CATEGORIES = ("L", "N", "P", "S", "Zs")
+
+
+def with_tone(ch, tone):
+    # Same as `with_tone` of the chars mode.
+    return ch[0] + tone + ch[1:].lstrip(VARIATION_SELECTOR)
+
+
+def read_emoji(path):
+    """Returns `[char, name, has_tones]` of fully qualified emoji."""
+    emoji = []
+    # including skin tone variants
+    qualified = set()
+    group = None
+    for line in Path(path).read_text().splitlines():
+        if line.startswith("# group:"):
+            group = line.split(":", 1)[1].strip()
+        if line.startswith("#") or "; fully-qualified" not in line:
+            continue
+
+        # `1F44B ; fully-qualified # 👋 E0.6 waving hand`
+        ch, _version, name = line.split("#", 1)[1].strip().split(" ", 2)
+        qualified.add(ch)
+        if group != "Component" and not any(tone in ch for tone in SKIN_TONES):
+            emoji.append([ch, name, False])
+
+    for e in emoji:
+        e[2] = with_tone(e[0], SKIN_TONES[0]) in qualified
+    return emoji
+
+
+def read_symbols(path, skip):
+    """Returns `[char, name]` of non-emoji characters of `BLOCKS`."""
+    symbols = []
+    for line in Path(path).read_text().splitlines():
+        code, name, category = line.split(";")[:3]
+        code = int(code, 16)
+        ch = chr(code)
+        if not any(start <= code <= end for start, end in BLOCKS):
+            continue
+        if not category.startswith(CATEGORIES) or name.startswith("<") or ch in skip:
+            continue
+        symbols.append([ch, name.lower()])
+    return symbols
+
+
+def read_keywords(paths):
+    keywords = {}
+    for path in paths:
+        data = json.loads(Path(path).read_text())
+        annotations = next(iter(data.values()))["annotations"]
+        for ch, annotation in annotations.items():
+            words = " ".join(annotation.get("default", [])).split()
+            keywords.setdefault(ch.replace(VARIATION_SELECTOR, ""), []).extend(words)
+
+    extra = Path(__file__).with_name("chars_keywords.tsv")
+    for line in extra.read_text().splitlines():
+        if line and not line.startswith("# "):
+            ch, words = line.split("\t")
+            # Extra keywords go first, as they are picked for common queries.
+            key = ch.replace(VARIATION_SELECTOR, "")
+            keywords[key] = words.split() + keywords.get(key, [])
+    return keywords
+
+
+def main(emoji_test, unicode_data, *annotations):
+    emoji = read_emoji(emoji_test)
+    skip = {e[0].replace(VARIATION_SELECTOR, "") for e in emoji}
+    symbols = read_symbols(unicode_data, skip)
+    keywords = read_keywords(annotations)
+
+    print("# Characters picked by the chars mode: `char<TAB>name<TAB>keywords[<TAB>tone]`,")
+    print("# where `tone` marks emoji supporting skin tone modifiers.")
+    print("# Generated by tools/chars_table.py, see it for the sources.")
+    for ch, name, *tone in emoji + symbols:
+        name_words = set(name.replace(":", "").split())
+        words = []
+        for word in keywords.get(ch.replace(VARIATION_SELECTOR, ""), []):
+            if word not in name_words and word not in words:
+                words.append(word)
+        fields = [ch, name, " ".join(words)] + (["tone"] if tone and tone[0] else [])
+        print("\t".join(fields))
+
+
+if __name__ == "__main__":
+    if len(sys.argv) < 3:
+        sys.exit(__doc__)
+    main(*sys.argv[1:])